@@ -0,0 +1,105 @@
+//! Derive macros for the [`bfbb`](https://docs.rs/bfbb) crate.
+//!
+//! These are re-exported by `bfbb` and should be used from there rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields};
+
+/// Derive `EndianAware` for a `#[repr(C)]` struct by swapping the bytes of each field individually.
+///
+/// Field offsets follow the `repr(C)` layout rules, so `#[repr(C, packed)]` and `#[repr(transparent)]` structs
+/// are also supported.
+#[proc_macro_derive(EndianAware)]
+pub fn derive_endian_aware(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.span(),
+            "EndianAware can only be derived for structs",
+        ));
+    };
+
+    let packed = repr_layout(input)?;
+
+    let types = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().map(|f| &f.ty).collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().map(|f| &f.ty).collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    let swaps = types.iter().map(|ty| {
+        let align = if packed {
+            quote!(1)
+        } else {
+            quote!(::core::mem::align_of::<#ty>())
+        };
+        quote! {
+            let align = #align;
+            offset = (offset + align - 1) / align * align;
+            let size = ::core::mem::size_of::<#ty>();
+            <#ty as ::bfbb::endian::EndianAware>::swap_endian(&mut bytes[offset..offset + size]);
+            offset += size;
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    for ty in &types {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ty: ::bfbb::endian::EndianAware));
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::bfbb::endian::EndianAware for #name #ty_generics #where_clause {
+            #[allow(unused_variables, unused_mut, unused_assignments)]
+            fn swap_endian(bytes: &mut [u8]) {
+                let mut offset = 0usize;
+                #(#swaps)*
+            }
+        }
+    })
+}
+
+/// Check that `input` has a layout with defined field offsets, returning whether it is packed.
+fn repr_layout(input: &DeriveInput) -> syn::Result<bool> {
+    let mut defined = false;
+    let mut packed = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") || meta.path.is_ident("transparent") {
+                defined = true;
+            } else if meta.path.is_ident("packed") {
+                if meta.input.peek(syn::token::Paren) {
+                    return Err(meta.error("EndianAware does not support `packed(N)`"));
+                }
+                packed = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream2>()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    if defined {
+        Ok(packed)
+    } else {
+        Err(Error::new(
+            input.ident.span(),
+            "EndianAware can only be derived for `#[repr(C)]` or `#[repr(transparent)]` structs",
+        ))
+    }
+}
@@ -0,0 +1,27 @@
+//! Utilities for working with the game's assets.
+//!
+//! Every asset is identified by an asset id, which is the [`str_hash`] of the asset's name. Entities created from an
+//! asset share its id, allowing them to be found by name at runtime.
+
+/// The game's string hashing function (`xStrHash`), used to generate asset ids from asset names.
+///
+/// Hashing is case-insensitive.
+///
+/// See also: [decomp](https://github.com/bfbbdecomp/bfbb/blob/master/src/Core/x/xString.cpp)
+///
+/// # Examples
+/// ```
+/// use bfbb::asset::str_hash;
+///
+/// assert_eq!(str_hash("A"), 0x41);
+/// assert_eq!(str_hash("AB"), 0x41 * 131 + 0x42);
+/// assert_eq!(str_hash("spatula"), str_hash("SPATULA"));
+/// ```
+#[must_use]
+pub fn str_hash(name: &str) -> u32 {
+    name.bytes().fold(0, |hash, c| {
+        // Convert lowercase characters to uppercase
+        let c = c - (c & (c >> 1) & 0x20);
+        hash.wrapping_mul(131).wrapping_add(c.into())
+    })
+}
@@ -0,0 +1,209 @@
+//! Decoding of (decrypted) Action Replay codes.
+//!
+//! Each line is a pair of 32-bit words, an address and a value. The top 7 bits of the address encode what the line
+//! does:
+//!
+//! ```text
+//! 31-30  subtype   what kind of write, or how many lines a failed condition skips
+//! 29-27  type      0 for writes, otherwise the comparison of a condition
+//! 26-25  size      8-bit, 16-bit, 32-bit or 32-bit float
+//! 24-0   address   offset from 0x80000000
+//! ```
+//!
+//! A line whose address is zero is a special "zero code", of which only the end of code (`00000000 00000000`) and
+//! normal execution (`00000000 40000000`) codes are supported.
+
+use super::DecodeError;
+
+/// The size of a value written or compared by an [`Instruction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Size {
+    /// An 8-bit value.
+    U8,
+    /// A 16-bit value.
+    U16,
+    /// A 32-bit value.
+    U32,
+    /// A 32-bit floating point value.
+    F32,
+}
+
+/// How the value in memory is compared to the code's value by an [`Instruction::If`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    /// `memory == value`
+    Equal,
+    /// `memory != value`
+    NotEqual,
+    /// `memory < value`, as signed integers.
+    LessSigned,
+    /// `memory > value`, as signed integers.
+    GreaterSigned,
+    /// `memory < value`, as unsigned integers.
+    LessUnsigned,
+    /// `memory > value`, as unsigned integers.
+    GreaterUnsigned,
+    /// `memory & value != 0`
+    And,
+}
+
+/// What is skipped when the condition of an [`Instruction::If`] is false.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Skip {
+    /// Skip the next line.
+    OneLine,
+    /// Skip the next two lines.
+    TwoLines,
+    /// Skip every line until an [`Instruction::Normal`].
+    UntilNormal,
+    /// Skip the rest of the code.
+    All,
+}
+
+/// A single line of an Action Replay code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Instruction {
+    /// Write `value` to `count` consecutive values starting at `address`.
+    Write {
+        /// The first address written to.
+        address: u32,
+        /// The size of each write.
+        size: Size,
+        /// The value written.
+        value: u32,
+        /// The number of consecutive values written.
+        count: u32,
+    },
+    /// Write `value` at `offset` bytes past the address stored at `pointer`.
+    WritePointer {
+        /// The address of the pointer.
+        pointer: u32,
+        /// The size of the write.
+        size: Size,
+        /// The offset from the pointer's value written to.
+        offset: u32,
+        /// The value written.
+        value: u32,
+    },
+    /// Add `value` to the value at `address`.
+    Add {
+        /// The address of the value added to.
+        address: u32,
+        /// The size of the value added to.
+        size: Size,
+        /// The value added.
+        value: u32,
+    },
+    /// Skip lines if the value at `address` does not compare to `value`.
+    If {
+        /// The address of the value compared.
+        address: u32,
+        /// The size of the value compared.
+        size: Size,
+        /// How the values are compared.
+        comparison: Comparison,
+        /// The value compared against.
+        value: u32,
+        /// What to skip if the condition is false.
+        skip: Skip,
+    },
+    /// A master code. These are only needed by a physical Action Replay and do nothing.
+    Master,
+    /// Resume executing lines after an [`Instruction::If`] with [`Skip::UntilNormal`].
+    Normal,
+    /// Stop executing the code.
+    End,
+}
+
+pub(super) fn decode(lines: &[(u32, u32)]) -> Result<Vec<Instruction>, DecodeError> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, &(addr, data))| {
+            if addr == 0 {
+                return match data {
+                    0 => Ok(Instruction::End),
+                    0x4000_0000 => Ok(Instruction::Normal),
+                    _ => Err(DecodeError::Unsupported(i)),
+                };
+            }
+
+            let subtype = addr >> 30;
+            let kind = (addr >> 27) & 0x7;
+            let size = match (addr >> 25) & 0x3 {
+                0 => Size::U8,
+                1 => Size::U16,
+                2 => Size::U32,
+                _ => Size::F32,
+            };
+            let address = (addr & 0x01FF_FFFF) | 0x8000_0000;
+            let value = match size {
+                Size::U8 => data & 0xFF,
+                Size::U16 => data & 0xFFFF,
+                Size::U32 | Size::F32 => data,
+            };
+
+            if kind != 0 {
+                let comparison = match kind {
+                    1 => Comparison::Equal,
+                    2 => Comparison::NotEqual,
+                    3 => Comparison::LessSigned,
+                    4 => Comparison::GreaterSigned,
+                    5 => Comparison::LessUnsigned,
+                    6 => Comparison::GreaterUnsigned,
+                    _ => Comparison::And,
+                };
+                let skip = match subtype {
+                    0 => Skip::OneLine,
+                    1 => Skip::TwoLines,
+                    2 => Skip::UntilNormal,
+                    _ => Skip::All,
+                };
+                return Ok(Instruction::If {
+                    address,
+                    size,
+                    comparison,
+                    value,
+                    skip,
+                });
+            }
+
+            Ok(match subtype {
+                0 => {
+                    // Floats are written in the same way as any other 32-bit value
+                    let (size, count) = match size {
+                        Size::U8 => (Size::U8, (data >> 8) + 1),
+                        Size::U16 => (Size::U16, (data >> 16) + 1),
+                        Size::U32 | Size::F32 => (Size::U32, 1),
+                    };
+                    Instruction::Write {
+                        address,
+                        size,
+                        value,
+                        count,
+                    }
+                }
+                1 => {
+                    let (size, offset) = match size {
+                        Size::U8 => (Size::U8, data >> 8),
+                        Size::U16 => (Size::U16, (data >> 16) * 2),
+                        Size::U32 | Size::F32 => (Size::U32, 0),
+                    };
+                    Instruction::WritePointer {
+                        pointer: address,
+                        size,
+                        offset,
+                        value,
+                    }
+                }
+                2 => Instruction::Add {
+                    address,
+                    size,
+                    value,
+                },
+                _ => Instruction::Master,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,264 @@
+use bytemuck::NoUninit;
+
+use crate::{
+    endian::EndianAware,
+    game_interface::dolphin::dolphin_var::{
+        task_menu_count_path, LAB_DOOR_ADDRESS, POWERS_ADDRESS, SHINY_COUNT_ADDRESS,
+        SOCK_COUNT_ADDRESS, SPATULA_COUNT_ADDRESS,
+    },
+    Spatula, TaskStatus,
+};
+
+use super::{CheatResult, Code, CodeFormat, Error};
+
+/// `DE000000 80008180`, only continue if `po` points into the game's memory.
+const CHECK_POINTER: (u32, u32) = (0xDE00_0000, 0x8000_8180);
+
+/// Builds a [`Code`] that writes values to the game's memory, for use in Dolphin's cheat manager.
+///
+/// Codes are run by the game every frame, so every write also freezes the value it writes.
+///
+/// # Examples
+/// ```
+/// use bfbb::cheat::{CodeBuilder, CodeFormat};
+///
+/// let code = CodeBuilder::new("Start with powers", CodeFormat::Gecko)
+///     .initial_bubble_bowl(true)
+///     .initial_cruise_bubble(true)
+///     .lab_door_cost(50)
+///     .build();
+/// assert_eq!(
+///     code.to_string(),
+///     "$Start with powers\n003C0F17 00000001\n003C0F18 00000001\n044F6CB8 00000032"
+/// );
+/// ```
+///
+/// Unlocking every task in the pause menu:
+/// ```
+/// use bfbb::cheat::{CodeBuilder, CodeFormat};
+/// use bfbb::{EnumCount, IntoEnumIterator, Spatula, TaskStatus};
+///
+/// let code = Spatula::iter()
+///     .fold(CodeBuilder::new("Unlock all tasks", CodeFormat::ActionReplay), |code, s| {
+///         code.task_status(s, TaskStatus::Incomplete)
+///     })
+///     .build();
+/// assert_eq!(code.lines().len(), Spatula::COUNT);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CodeBuilder {
+    name: String,
+    format: CodeFormat,
+    lines: Vec<(u32, u32)>,
+}
+
+impl CodeBuilder {
+    /// Start building a new code named `name`.
+    pub fn new(name: impl Into<String>, format: CodeFormat) -> Self {
+        Self {
+            name: name.into(),
+            format,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Write `value` to the location given by the pointer-path `path`.
+    ///
+    /// See [`InterfaceBackend::create_var`](crate::game_interface::game_var::InterfaceBackend::create_var) for how
+    /// `path` is followed. Gecko codes support any path, Action Replay codes only support paths with at most one
+    /// pointer and with limited offsets.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error::UnsupportedPath`] if `path` can not be written by a code of this format.
+    pub fn write<T: NoUninit + EndianAware>(
+        mut self,
+        path: &[usize],
+        value: T,
+    ) -> CheatResult<Self> {
+        let mut bytes = bytemuck::bytes_of(&value).to_vec();
+        T::swap_endian(&mut bytes);
+
+        let lines = match self.format {
+            CodeFormat::ActionReplay => action_replay_write(path, &bytes),
+            CodeFormat::Gecko => gecko_write(path, &bytes),
+        };
+        let lines = lines.ok_or_else(|| Error::UnsupportedPath {
+            path: path.to_vec(),
+            format: self.format,
+        })?;
+        self.lines.extend(lines);
+        Ok(self)
+    }
+
+    /// Write a value to a location from the crate's address table, which is always supported.
+    fn write_known<T: NoUninit + EndianAware>(self, path: &[usize], value: T) -> Self {
+        self.write(path, value)
+            .expect("Known addresses should be supported by every code format")
+    }
+
+    /// Set the spatula counter.
+    #[must_use]
+    pub fn spatula_count(self, count: u32) -> Self {
+        self.write_known(&[SPATULA_COUNT_ADDRESS], count)
+    }
+
+    /// Set the shiny object counter.
+    #[must_use]
+    pub fn shiny_count(self, count: u32) -> Self {
+        self.write_known(&[SHINY_COUNT_ADDRESS], count)
+    }
+
+    /// Set the total number of Patrick's socks collected.
+    #[must_use]
+    pub fn sock_count(self, count: u32) -> Self {
+        self.write_known(&[SOCK_COUNT_ADDRESS], count)
+    }
+
+    /// Set the number of spatulas required to open the door to Chum Bucket Lab.
+    #[must_use]
+    pub fn lab_door_cost(self, cost: u32) -> Self {
+        self.write_known(&[LAB_DOOR_ADDRESS], cost)
+    }
+
+    /// Set whether the player has the bubble bowl.
+    #[must_use]
+    pub fn bubble_bowl(self, unlocked: bool) -> Self {
+        self.write_known(&[POWERS_ADDRESS], unlocked)
+    }
+
+    /// Set whether the player has the cruise bubble.
+    #[must_use]
+    pub fn cruise_bubble(self, unlocked: bool) -> Self {
+        self.write_known(&[POWERS_ADDRESS + 1], unlocked)
+    }
+
+    /// Set whether the player starts a new game with the bubble bowl.
+    #[must_use]
+    pub fn initial_bubble_bowl(self, unlocked: bool) -> Self {
+        self.write_known(&[POWERS_ADDRESS + 2], unlocked)
+    }
+
+    /// Set whether the player starts a new game with the cruise bubble.
+    #[must_use]
+    pub fn initial_cruise_bubble(self, unlocked: bool) -> Self {
+        self.write_known(&[POWERS_ADDRESS + 3], unlocked)
+    }
+
+    /// Set the status of `spatula`'s task in the pause menu.
+    ///
+    /// **NOTE:** As the status is written every frame, collecting the spatula will not change its status.
+    #[must_use]
+    pub fn task_status(self, spatula: Spatula, status: TaskStatus) -> Self {
+        self.write_known(&task_menu_count_path(spatula), i16::from(status))
+    }
+
+    /// Finish building the code.
+    #[must_use]
+    pub fn build(self) -> Code {
+        Code::new(self.name, self.format, self.lines)
+            .expect("Generated codes should always be valid")
+    }
+}
+
+/// Split `bytes` into chunks of the largest size a single line can write, yielding their offsets and sizes.
+fn chunks(bytes: &[u8]) -> impl Iterator<Item = (u32, &[u8])> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let rest = &bytes[offset..];
+        let len = match rest.len() {
+            0 => return None,
+            1 => 1,
+            2 | 3 => 2,
+            _ => 4,
+        };
+        let chunk = (offset as u32, &rest[..len]);
+        offset += len;
+        Some(chunk)
+    })
+}
+
+fn be_value(chunk: &[u8]) -> u32 {
+    chunk.iter().fold(0, |acc, &b| acc << 8 | u32::from(b))
+}
+
+/// The offset of `addr` from the start of memory as addressed by a code, if it's in range.
+fn code_offset(addr: usize) -> Option<u32> {
+    let offset = u32::try_from(addr).ok()?.checked_sub(0x8000_0000)?;
+    (offset < 0x0200_0000).then_some(offset)
+}
+
+fn gecko_write(path: &[usize], bytes: &[u8]) -> Option<Vec<(u32, u32)>> {
+    let (&last, pointers) = path.split_last()?;
+    let mut lines = Vec::new();
+
+    // Load each pointer into `po`, checking that it is valid before continuing
+    let po_flag = if let Some((&first, rest)) = pointers.split_first() {
+        lines.push((0x4800_0000, u32::try_from(first).ok()?));
+        lines.push(CHECK_POINTER);
+        for &offset in rest {
+            lines.push((0x5801_0000, u32::try_from(offset).ok()?));
+            lines.push(CHECK_POINTER);
+        }
+        0x1000_0000
+    } else {
+        0
+    };
+    let base = if pointers.is_empty() {
+        code_offset(last)?
+    } else {
+        u32::try_from(last).ok().filter(|&o| o < 0x0200_0000)?
+    };
+
+    for (offset, chunk) in chunks(bytes) {
+        let codetype: u32 = match chunk.len() {
+            1 => 0x00,
+            2 => 0x02,
+            _ => 0x04,
+        };
+        let addr = (base + offset) & 0x01FF_FFFF;
+        lines.push(((codetype << 24) | po_flag | addr, be_value(chunk)));
+    }
+
+    if !pointers.is_empty() {
+        lines.push((0xE200_0000 | pointers.len() as u32, 0));
+    }
+    Some(lines)
+}
+
+fn action_replay_write(path: &[usize], bytes: &[u8]) -> Option<Vec<(u32, u32)>> {
+    match *path {
+        [addr] => {
+            let base = code_offset(addr)?;
+            chunks(bytes)
+                .map(|(offset, chunk)| {
+                    let size = (chunk.len() as u32 / 2) << 25;
+                    let addr = size | ((base + offset) & 0x01FF_FFFF);
+                    // An address of zero would be read as a zero code
+                    (addr != 0).then_some((addr, be_value(chunk)))
+                })
+                .collect()
+        }
+        [pointer, offset] => {
+            let pointer = code_offset(pointer)?;
+            let offset = u32::try_from(offset).ok()?;
+            chunks(bytes)
+                .map(|(o, chunk)| {
+                    let offset = offset + o;
+                    let value = be_value(chunk);
+                    let (size, data) = match chunk.len() {
+                        1 if offset < 1 << 24 => (0, offset << 8 | value),
+                        2 if offset % 2 == 0 && offset / 2 < 1 << 16 => {
+                            (1, (offset / 2) << 16 | value)
+                        }
+                        // 32-bit pointer writes can't be offset
+                        4 if offset == 0 => (2, value),
+                        _ => return None,
+                    };
+                    Some((1 << 30 | size << 25 | pointer, data))
+                })
+                .collect()
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,279 @@
+//! Decoding of Gecko codes.
+//!
+//! Gecko codes are executed by a code handler injected into the game. Each line is a pair of 32-bit words where the
+//! top byte of the first word is the codetype. Most codetypes address memory relative to one of two registers, the
+//! base address (`ba`) and the pointer offset (`po`), both of which start at `0x80000000`.
+//!
+//! Only codetypes which read or write memory are supported. Codetypes that insert or branch to PowerPC instructions
+//! (e.g. `C2`) can't be run outside of the game and are rejected.
+
+use super::DecodeError;
+
+/// One of the code handler's address registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Register {
+    /// The base address, `ba`.
+    BaseAddress,
+    /// The pointer offset, `po`.
+    Pointer,
+}
+
+/// An address relative to one of the code handler's registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address {
+    /// The register the address is relative to.
+    pub register: Register,
+    /// The offset from the register's value.
+    pub offset: u32,
+}
+
+/// The size of a value written or compared by an [`Instruction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Size {
+    /// An 8-bit value.
+    U8,
+    /// A 16-bit value.
+    U16,
+    /// A 32-bit value.
+    U32,
+}
+
+/// How the value in memory is compared by an [`Instruction::If`]. Comparisons are unsigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    /// The value in memory is equal to the code's value.
+    Equal,
+    /// The value in memory is not equal to the code's value.
+    NotEqual,
+    /// The value in memory is greater than the code's value.
+    Greater,
+    /// The value in memory is less than the code's value.
+    Less,
+}
+
+/// A single operation of a Gecko code, which may span multiple lines.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Instruction {
+    /// Write `value` to `count` consecutive values starting at `address` (codetypes `00`, `02`, `04`).
+    Write {
+        /// The first address written to.
+        address: Address,
+        /// The size of each write.
+        size: Size,
+        /// The value written.
+        value: u32,
+        /// The number of consecutive values written.
+        count: u32,
+    },
+    /// Write `bytes` starting at `address` (codetype `06`).
+    WriteBytes {
+        /// The first address written to.
+        address: Address,
+        /// The bytes written.
+        bytes: Vec<u8>,
+    },
+    /// Only execute the following instructions, up to the matching [`Instruction::Endif`], if
+    /// `(memory & !mask) <comparison> value` (codetypes `20`-`2E`).
+    If {
+        /// Apply a single endif before this condition, closing the previous one.
+        endif: bool,
+        /// The address of the value compared.
+        address: Address,
+        /// The size of the value compared.
+        size: Size,
+        /// How the values are compared.
+        comparison: Comparison,
+        /// The value compared against.
+        value: u32,
+        /// Bits of the value in memory to ignore.
+        mask: u32,
+    },
+    /// Only execute the following instructions if `lower <= po < upper` (codetype `DE`).
+    CheckPointer {
+        /// The lowest valid pointer.
+        lower: u32,
+        /// One past the highest valid pointer.
+        upper: u32,
+    },
+    /// Load a register from memory, `register (+)= [address (+ offset_by)]` (codetypes `40`, `48`, `50`, `58`).
+    Load {
+        /// The register loaded.
+        register: Register,
+        /// Add to the register instead of replacing it.
+        add: bool,
+        /// The address loaded from.
+        address: u32,
+        /// A register added to `address`.
+        offset_by: Option<Register>,
+    },
+    /// Set a register to a value, `register (+)= value (+ offset_by)` (codetypes `42`, `4A`, `52`, `5A`).
+    Set {
+        /// The register set.
+        register: Register,
+        /// Add to the register instead of replacing it.
+        add: bool,
+        /// The value set.
+        value: u32,
+        /// A register added to `value`.
+        offset_by: Option<Register>,
+    },
+    /// Close `count` conditions, then optionally invert the enclosing one (codetype `E2`).
+    Endif {
+        /// The number of conditions closed.
+        count: u8,
+        /// Invert the enclosing condition, as an `else`.
+        invert: bool,
+        /// The new value of `ba`, if changed.
+        base_address: Option<u32>,
+        /// The new value of `po`, if changed.
+        pointer: Option<u32>,
+    },
+    /// Close every condition (codetype `E0`).
+    Terminator {
+        /// The new value of `ba`, if changed.
+        base_address: Option<u32>,
+        /// The new value of `po`, if changed.
+        pointer: Option<u32>,
+    },
+    /// Stop executing the code (codetype `F0`).
+    End,
+}
+
+pub(super) fn decode(lines: &[(u32, u32)]) -> Result<Vec<Instruction>, DecodeError> {
+    let mut instructions = Vec::with_capacity(lines.len());
+    let mut i = 0;
+    while i < lines.len() {
+        let (first, data) = lines[i];
+        let codetype = (first >> 24) as u8;
+        let register = if codetype & 0x10 == 0 {
+            Register::BaseAddress
+        } else {
+            Register::Pointer
+        };
+        let address = Address {
+            register,
+            offset: first & 0x01FF_FFFF,
+        };
+        let high_register = |half: u32| (half != 0).then_some(half << 16);
+
+        let instruction = match codetype & !0x11 {
+            _ if codetype >= 0x40 => match codetype {
+                0x40 | 0x42 | 0x48 | 0x4A | 0x50 | 0x52 | 0x58 | 0x5A => {
+                    let register = if codetype & 0x08 == 0 {
+                        Register::BaseAddress
+                    } else {
+                        Register::Pointer
+                    };
+                    let add = match (first >> 20) & 0xF {
+                        0 => false,
+                        1 => true,
+                        _ => return Err(DecodeError::Unsupported(i)),
+                    };
+                    // `4x` codetypes add `ba`, `5x` codetypes add `po`
+                    let offset_by = match (first >> 16) & 0xF {
+                        0 => None,
+                        1 if codetype & 0x10 == 0 => Some(Register::BaseAddress),
+                        1 => Some(Register::Pointer),
+                        _ => return Err(DecodeError::Unsupported(i)),
+                    };
+                    // Adding a gecko register (`Z`) isn't supported
+                    if first & 0xFFFF != 0 {
+                        return Err(DecodeError::Unsupported(i));
+                    }
+                    if codetype & 0x02 == 0 {
+                        Instruction::Load {
+                            register,
+                            add,
+                            address: data,
+                            offset_by,
+                        }
+                    } else {
+                        Instruction::Set {
+                            register,
+                            add,
+                            value: data,
+                            offset_by,
+                        }
+                    }
+                }
+                0xDE => Instruction::CheckPointer {
+                    lower: data & 0xFFFF_0000,
+                    upper: data << 16,
+                },
+                0xE0 => Instruction::Terminator {
+                    base_address: high_register(data >> 16),
+                    pointer: high_register(data & 0xFFFF),
+                },
+                0xE2 => Instruction::Endif {
+                    count: first as u8,
+                    invert: (first >> 20) & 0xF == 1,
+                    base_address: high_register(data >> 16),
+                    pointer: high_register(data & 0xFFFF),
+                },
+                0xF0 => Instruction::End,
+                _ => return Err(DecodeError::Unsupported(i)),
+            },
+            0x00 => Instruction::Write {
+                address,
+                size: Size::U8,
+                value: data & 0xFF,
+                count: (data >> 16) + 1,
+            },
+            0x02 => Instruction::Write {
+                address,
+                size: Size::U16,
+                value: data & 0xFFFF,
+                count: (data >> 16) + 1,
+            },
+            0x04 => Instruction::Write {
+                address,
+                size: Size::U32,
+                value: data,
+                count: 1,
+            },
+            0x06 => {
+                let len = data as usize;
+                let data_lines = (len + 7) / 8;
+                let Some(data) = lines.get(i + 1..i + 1 + data_lines) else {
+                    return Err(DecodeError::Truncated);
+                };
+                let mut bytes: Vec<u8> = data
+                    .iter()
+                    .flat_map(|&(a, b)| a.to_be_bytes().into_iter().chain(b.to_be_bytes()))
+                    .collect();
+                bytes.truncate(len);
+                i += data_lines;
+                Instruction::WriteBytes { address, bytes }
+            }
+            0x20 | 0x22 | 0x24 | 0x26 | 0x28 | 0x2A | 0x2C | 0x2E => {
+                let comparison = match codetype & 0x06 {
+                    0x00 => Comparison::Equal,
+                    0x02 => Comparison::NotEqual,
+                    0x04 => Comparison::Greater,
+                    _ => Comparison::Less,
+                };
+                let (size, value, mask) = if codetype & 0x08 == 0 {
+                    (Size::U32, data, 0)
+                } else {
+                    (Size::U16, data & 0xFFFF, data >> 16)
+                };
+                Instruction::If {
+                    endif: address.offset & 1 != 0,
+                    address: Address {
+                        offset: address.offset & !1,
+                        ..address
+                    },
+                    size,
+                    comparison,
+                    value,
+                    mask,
+                }
+            }
+            _ => return Err(DecodeError::Unsupported(i)),
+        };
+        instructions.push(instruction);
+        i += 1;
+    }
+    Ok(instructions)
+}
@@ -0,0 +1,288 @@
+//! Parsing of Action Replay and Gecko cheat codes.
+//!
+//! Codes can be read from plain code lists with [`parse_codes`], or from the `[ActionReplay]` and `[Gecko]`
+//! sections of a Dolphin game INI with [`parse_ini`]. With the `game-interface` feature enabled, parsed codes can be
+//! run against a running game with a `CodeRunner`, and new codes can be generated from the crate's known addresses
+//! with a `CodeBuilder`.
+//!
+//! # Examples
+//! ```
+//! use bfbb::cheat::{self, CodeFormat};
+//!
+//! # fn main() -> Result<(), cheat::Error> {
+//! let ini = "\
+//! [Gecko]
+//! $75 Spatulas
+//! 043C205C 0000004B
+//! *Sets the spatula counter.
+//! [Gecko_Enabled]
+//! $75 Spatulas
+//! ";
+//! let codes = cheat::parse_ini(ini)?;
+//! assert_eq!(codes.len(), 1);
+//! assert_eq!(codes[0].name(), "75 Spatulas");
+//! assert_eq!(codes[0].format(), CodeFormat::Gecko);
+//! assert_eq!(codes[0].lines(), &[(0x043C_205C, 0x0000_004B)]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+pub mod action_replay;
+#[cfg(feature = "game-interface")]
+mod export;
+pub mod gecko;
+#[cfg(feature = "game-interface")]
+mod runner;
+
+#[cfg(feature = "game-interface")]
+pub use export::CodeBuilder;
+#[cfg(feature = "game-interface")]
+pub use runner::CodeRunner;
+
+/// Result type for parsing cheat codes.
+pub type CheatResult<T> = std::result::Result<T, Error>;
+
+/// Error type for failures to parse cheat codes.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// A line is not a pair of 8-digit hexadecimal words.
+    #[error("Line {0} is not a valid code line")]
+    Syntax(usize),
+    /// A line is an encrypted Action Replay code, which must be decrypted first.
+    #[error("Line {0} is an encrypted Action Replay code")]
+    Encrypted(usize),
+    /// A code uses a codetype that can't be run outside of the game.
+    #[error("Code '{code}' uses an unsupported codetype on line {line}")]
+    Unsupported {
+        /// The name of the code.
+        code: String,
+        /// The line of the code (starting at 1) with the unsupported codetype.
+        line: usize,
+    },
+    /// A code ended in the middle of a codetype spanning multiple lines.
+    #[error("Code '{0}' ends unexpectedly")]
+    Truncated(String),
+    /// A pointer-path can not be written to by a code of the given format.
+    #[error("Path {path:X?} can not be written by a {format:?} code")]
+    UnsupportedPath {
+        /// The pointer-path written to.
+        path: Vec<usize>,
+        /// The format of the code.
+        format: CodeFormat,
+    },
+}
+
+/// Decoding failure, without the context of which code failed.
+#[derive(Debug)]
+pub(crate) enum DecodeError {
+    /// The index of the line with an unsupported codetype.
+    Unsupported(usize),
+    Truncated,
+}
+
+/// The format of a cheat code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeFormat {
+    /// A decrypted Action Replay code.
+    ActionReplay,
+    /// A Gecko code.
+    Gecko,
+}
+
+/// The decoded instructions of a [`Code`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Instructions {
+    /// The instructions of an Action Replay code.
+    ActionReplay(Vec<action_replay::Instruction>),
+    /// The instructions of a Gecko code.
+    Gecko(Vec<gecko::Instruction>),
+}
+
+/// A named cheat code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Code {
+    name: String,
+    lines: Vec<(u32, u32)>,
+    instructions: Instructions,
+}
+
+impl Code {
+    /// Create a new code from its raw lines.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error::Unsupported`] or [`Error::Truncated`] if the lines can not be decoded.
+    pub fn new(
+        name: impl Into<String>,
+        format: CodeFormat,
+        lines: impl Into<Vec<(u32, u32)>>,
+    ) -> CheatResult<Self> {
+        let name = name.into();
+        let lines = lines.into();
+        let instructions = match format {
+            CodeFormat::ActionReplay => {
+                action_replay::decode(&lines).map(Instructions::ActionReplay)
+            }
+            CodeFormat::Gecko => gecko::decode(&lines).map(Instructions::Gecko),
+        };
+        let instructions = match instructions {
+            Ok(i) => i,
+            Err(DecodeError::Unsupported(line)) => {
+                return Err(Error::Unsupported {
+                    code: name,
+                    line: line + 1,
+                })
+            }
+            Err(DecodeError::Truncated) => return Err(Error::Truncated(name)),
+        };
+        Ok(Self {
+            name,
+            lines,
+            instructions,
+        })
+    }
+
+    /// The name of this code.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The format of this code.
+    #[must_use]
+    pub fn format(&self) -> CodeFormat {
+        match self.instructions {
+            Instructions::ActionReplay(_) => CodeFormat::ActionReplay,
+            Instructions::Gecko(_) => CodeFormat::Gecko,
+        }
+    }
+
+    /// The raw lines of this code.
+    #[must_use]
+    pub fn lines(&self) -> &[(u32, u32)] {
+        &self.lines
+    }
+
+    /// The decoded instructions of this code.
+    #[must_use]
+    pub fn instructions(&self) -> &Instructions {
+        &self.instructions
+    }
+}
+
+/// Formats a code as it would appear in a code list, with its name on the first line.
+impl Display for Code {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${}", self.name)?;
+        for (a, b) in &self.lines {
+            write!(f, "\n{a:08X} {b:08X}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a list of codes in a single format.
+///
+/// Each code begins with a line containing its name, prefixed with `$`, followed by its lines. Lines starting with
+/// `*` are notes and are ignored, as are blank lines.
+///
+/// # Errors
+///
+/// Will return an [`Error`] if any line is malformed or any code can not be decoded.
+pub fn parse_codes(text: &str, format: CodeFormat) -> CheatResult<Vec<Code>> {
+    let mut codes = Vec::new();
+    parse_lines(text.lines().enumerate(), format, &mut codes)?;
+    Ok(codes)
+}
+
+/// Parse the `[ActionReplay]` and `[Gecko]` sections of a Dolphin game INI, such as `GQPE78.ini`.
+///
+/// Every other section, including which codes are enabled, is ignored.
+///
+/// # Errors
+///
+/// Will return an [`Error`] if any line of a code section is malformed or any code can not be decoded.
+pub fn parse_ini(text: &str) -> CheatResult<Vec<Code>> {
+    let mut codes = Vec::new();
+    let mut section = None;
+    let mut lines = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            if let Some(format) = section {
+                parse_lines(lines.drain(..), format, &mut codes)?;
+            }
+            section = match &trimmed[1..trimmed.len() - 1] {
+                "ActionReplay" => Some(CodeFormat::ActionReplay),
+                "Gecko" => Some(CodeFormat::Gecko),
+                _ => None,
+            };
+        } else if section.is_some() {
+            lines.push((i, line));
+        }
+    }
+    if let Some(format) = section {
+        parse_lines(lines.drain(..), format, &mut codes)?;
+    }
+    Ok(codes)
+}
+
+fn parse_lines<'a>(
+    lines: impl Iterator<Item = (usize, &'a str)>,
+    format: CodeFormat,
+    codes: &mut Vec<Code>,
+) -> CheatResult<()> {
+    let mut current: Option<(String, Vec<(u32, u32)>)> = None;
+    for (i, line) in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('*') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('$') {
+            if let Some((name, lines)) = current.take() {
+                codes.push(Code::new(name, format, lines)?);
+            }
+            current = Some((name.trim().to_owned(), Vec::new()));
+            continue;
+        }
+
+        let code_line = parse_line(line).ok_or_else(|| {
+            if is_encrypted(line) {
+                Error::Encrypted(i + 1)
+            } else {
+                Error::Syntax(i + 1)
+            }
+        })?;
+        current
+            .get_or_insert_with(|| (String::new(), Vec::new()))
+            .1
+            .push(code_line);
+    }
+    if let Some((name, lines)) = current {
+        codes.push(Code::new(name, format, lines)?);
+    }
+    Ok(())
+}
+
+fn parse_line(line: &str) -> Option<(u32, u32)> {
+    let mut words = line.split_whitespace().map(|w| {
+        (w.len() == 8)
+            .then(|| u32::from_str_radix(w, 16).ok())
+            .flatten()
+    });
+    match (words.next(), words.next(), words.next()) {
+        (Some(Some(a)), Some(Some(b)), None) => Some((a, b)),
+        _ => None,
+    }
+}
+
+/// Encrypted Action Replay codes are written as `XXXX-XXXX-XXXXX`.
+fn is_encrypted(line: &str) -> bool {
+    let parts: Vec<_> = line.split('-').map(str::len).collect();
+    parts == [4, 4, 5]
+}
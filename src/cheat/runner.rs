@@ -0,0 +1,422 @@
+use crate::game_interface::{
+    game_var::InterfaceBackend, GameInterface, InterfaceError, InterfaceResult,
+};
+
+use super::{
+    action_replay::{self, Skip},
+    gecko::{self, Register},
+    Code, Instructions,
+};
+
+/// Runs a set of [`Code`]s against a [`GameInterface`].
+///
+/// Like the game's code handler, codes should be applied once per frame by calling [`CodeRunner::apply`] in a loop.
+///
+/// **NOTE:** Unlike the game's code handler, the Gecko registers are reset before each code and execution is not
+/// synchronized with the game. Codes that must take effect before the game reads a value should still be run by
+/// Dolphin.
+///
+/// # Examples
+/// ```
+/// use bfbb::cheat::{self, CodeFormat, CodeRunner};
+/// use bfbb::game_interface::mock::MockInterface;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let codes = cheat::parse_codes("$75 Spatulas\n043C205C 0000004B", CodeFormat::Gecko)?;
+/// let runner = CodeRunner::new(codes);
+///
+/// let mut interface = MockInterface::default();
+/// runner.apply(&mut interface)?;
+///
+/// let mut spatulas = [0; 4];
+/// interface.read_bytes(0x803C_205C, &mut spatulas)?;
+/// assert_eq!(u32::from_be_bytes(spatulas), 75);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CodeRunner {
+    codes: Vec<(Code, bool)>,
+}
+
+impl CodeRunner {
+    /// Create a new runner with every code in `codes` enabled.
+    pub fn new(codes: impl IntoIterator<Item = Code>) -> Self {
+        Self {
+            codes: codes.into_iter().map(|c| (c, true)).collect(),
+        }
+    }
+
+    /// Add a code to this runner, enabled.
+    pub fn push(&mut self, code: Code) {
+        self.codes.push((code, true));
+    }
+
+    /// Iterate over this runner's codes.
+    pub fn codes(&self) -> impl Iterator<Item = &Code> {
+        self.codes.iter().map(|(c, _)| c)
+    }
+
+    /// Enable or disable every code named `name`, returning `false` if there are no such codes.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for (_, e) in self.codes.iter_mut().filter(|(c, _)| c.name() == name) {
+            *e = enabled;
+            found = true;
+        }
+        found
+    }
+
+    /// Returns `true` if any code named `name` is enabled.
+    #[must_use]
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.codes.iter().any(|(c, e)| *e && c.name() == name)
+    }
+
+    /// Run every enabled code once.
+    ///
+    /// A code that accesses an address outside of the game's memory stops running, but the remaining codes are still
+    /// run.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`] if the implementation is unable to access the game.
+    pub fn apply<F: InterfaceBackend>(
+        &self,
+        interface: &mut GameInterface<F>,
+    ) -> InterfaceResult<()> {
+        for (code, _) in self.codes.iter().filter(|(_, e)| *e) {
+            let result = match code.instructions() {
+                Instructions::ActionReplay(i) => run_action_replay(i, interface),
+                Instructions::Gecko(i) => run_gecko(i, interface),
+            };
+            match result {
+                Err(InterfaceError::InvalidPointer(addr)) => {
+                    log::debug!(
+                        "Code '{}' accessed invalid address {addr:#010X}",
+                        code.name()
+                    );
+                }
+                r => r?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read<F: InterfaceBackend, const N: usize>(
+    interface: &GameInterface<F>,
+    addr: u32,
+) -> InterfaceResult<[u8; N]> {
+    let mut buf = [0; N];
+    interface.read_bytes(addr, &mut buf)?;
+    Ok(buf)
+}
+
+fn read_u32<F: InterfaceBackend>(interface: &GameInterface<F>, addr: u32) -> InterfaceResult<u32> {
+    read(interface, addr).map(u32::from_be_bytes)
+}
+
+/// Read the value of `len` bytes at `addr`, zero-extended.
+fn read_sized<F: InterfaceBackend>(
+    interface: &GameInterface<F>,
+    addr: u32,
+    len: usize,
+) -> InterfaceResult<u32> {
+    let mut buf = [0; 4];
+    interface.read_bytes(addr, &mut buf[4 - len..])?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+/// Write the low `len` bytes of `value` to `count` consecutive values starting at `addr`.
+fn write_sized<F: InterfaceBackend>(
+    interface: &mut GameInterface<F>,
+    addr: u32,
+    len: usize,
+    value: u32,
+    count: u32,
+) -> InterfaceResult<()> {
+    let bytes = &value.to_be_bytes()[4 - len..];
+    for i in 0..count {
+        interface.write_bytes(addr.wrapping_add(i * len as u32), bytes)?;
+    }
+    Ok(())
+}
+
+fn ar_len(size: action_replay::Size) -> usize {
+    match size {
+        action_replay::Size::U8 => 1,
+        action_replay::Size::U16 => 2,
+        action_replay::Size::U32 | action_replay::Size::F32 => 4,
+    }
+}
+
+fn run_action_replay<F: InterfaceBackend>(
+    instructions: &[action_replay::Instruction],
+    interface: &mut GameInterface<F>,
+) -> InterfaceResult<()> {
+    use action_replay::{Comparison, Instruction, Size};
+
+    let mut skip_lines = 0;
+    let mut skip_until_normal = false;
+    for instruction in instructions {
+        if skip_lines > 0 {
+            skip_lines -= 1;
+            continue;
+        }
+        if skip_until_normal {
+            skip_until_normal = *instruction != Instruction::Normal;
+            continue;
+        }
+
+        match *instruction {
+            Instruction::Write {
+                address,
+                size,
+                value,
+                count,
+            } => write_sized(interface, address, ar_len(size), value, count)?,
+            Instruction::WritePointer {
+                pointer,
+                size,
+                offset,
+                value,
+            } => {
+                let addr = read_u32(interface, pointer)?.wrapping_add(offset);
+                write_sized(interface, addr, ar_len(size), value, 1)?;
+            }
+            Instruction::Add {
+                address,
+                size,
+                value,
+            } => {
+                let len = ar_len(size);
+                let current = read_sized(interface, address, len)?;
+                let new = match size {
+                    // The value is added as an integer, not reinterpreted as a float
+                    Size::F32 => (f32::from_bits(current) + value as f32).to_bits(),
+                    _ => current.wrapping_add(value),
+                };
+                write_sized(interface, address, len, new, 1)?;
+            }
+            Instruction::If {
+                address,
+                size,
+                comparison,
+                value,
+                skip,
+            } => {
+                let len = ar_len(size);
+                let memory = read_sized(interface, address, len)?;
+                // Sign-extend both values for signed comparisons
+                let shift = 32 - 8 * len as u32;
+                let signed = |v: u32| ((v << shift) as i32) >> shift;
+                let result = match (size, comparison) {
+                    (_, Comparison::Equal) => memory == value,
+                    (_, Comparison::NotEqual) => memory != value,
+                    (Size::F32, Comparison::LessSigned | Comparison::LessUnsigned) => {
+                        f32::from_bits(memory) < f32::from_bits(value)
+                    }
+                    (Size::F32, Comparison::GreaterSigned | Comparison::GreaterUnsigned) => {
+                        f32::from_bits(memory) > f32::from_bits(value)
+                    }
+                    (_, Comparison::LessSigned) => signed(memory) < signed(value),
+                    (_, Comparison::GreaterSigned) => signed(memory) > signed(value),
+                    (_, Comparison::LessUnsigned) => memory < value,
+                    (_, Comparison::GreaterUnsigned) => memory > value,
+                    (_, Comparison::And) => memory & value != 0,
+                };
+                if !result {
+                    match skip {
+                        Skip::OneLine => skip_lines = 1,
+                        Skip::TwoLines => skip_lines = 2,
+                        Skip::UntilNormal => skip_until_normal = true,
+                        Skip::All => break,
+                    }
+                }
+            }
+            Instruction::Master | Instruction::Normal => {}
+            Instruction::End => break,
+        }
+    }
+    Ok(())
+}
+
+/// The state of the Gecko code handler while running a code.
+struct GeckoState {
+    base_address: u32,
+    pointer: u32,
+    /// The number of conditions currently open.
+    depth: u32,
+    /// The depth of the outermost false condition, if any.
+    false_at: Option<u32>,
+}
+
+impl GeckoState {
+    fn register(&self, register: Register) -> u32 {
+        match register {
+            Register::BaseAddress => self.base_address,
+            Register::Pointer => self.pointer,
+        }
+    }
+
+    fn register_mut(&mut self, register: Register) -> &mut u32 {
+        match register {
+            Register::BaseAddress => &mut self.base_address,
+            Register::Pointer => &mut self.pointer,
+        }
+    }
+
+    fn address(&self, address: gecko::Address) -> u32 {
+        self.register(address.register).wrapping_add(address.offset)
+    }
+
+    fn push_condition(&mut self, result: bool) {
+        self.depth += 1;
+        if !result && self.false_at.is_none() {
+            self.false_at = Some(self.depth);
+        }
+    }
+
+    fn endif(&mut self) {
+        if self.false_at == Some(self.depth) {
+            self.false_at = None;
+        }
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Invert the innermost condition, as an `else`.
+    fn invert(&mut self) {
+        if self.false_at == Some(self.depth) {
+            self.false_at = None;
+        } else if self.false_at.is_none() && self.depth > 0 {
+            self.false_at = Some(self.depth);
+        }
+    }
+
+    fn set_registers(&mut self, base_address: Option<u32>, pointer: Option<u32>) {
+        if let Some(ba) = base_address {
+            self.base_address = ba;
+        }
+        if let Some(po) = pointer {
+            self.pointer = po;
+        }
+    }
+}
+
+fn run_gecko<F: InterfaceBackend>(
+    instructions: &[gecko::Instruction],
+    interface: &mut GameInterface<F>,
+) -> InterfaceResult<()> {
+    use gecko::{Comparison, Instruction, Size};
+
+    let mut state = GeckoState {
+        base_address: 0x8000_0000,
+        pointer: 0x8000_0000,
+        depth: 0,
+        false_at: None,
+    };
+    for instruction in instructions {
+        let active = state.false_at.is_none();
+        match instruction {
+            Instruction::If {
+                endif,
+                address,
+                size,
+                comparison,
+                value,
+                mask,
+            } => {
+                if *endif {
+                    state.endif();
+                }
+                let result = state.false_at.is_none() && {
+                    let len = if *size == Size::U16 { 2 } else { 4 };
+                    let memory = read_sized(interface, state.address(*address), len)? & !mask;
+                    match comparison {
+                        Comparison::Equal => memory == *value,
+                        Comparison::NotEqual => memory != *value,
+                        Comparison::Greater => memory > *value,
+                        Comparison::Less => memory < *value,
+                    }
+                };
+                state.push_condition(result);
+            }
+            Instruction::CheckPointer { lower, upper } => {
+                state.push_condition(active && (*lower..*upper).contains(&state.pointer));
+            }
+            Instruction::Endif {
+                count,
+                invert,
+                base_address,
+                pointer,
+            } => {
+                for _ in 0..*count {
+                    state.endif();
+                }
+                if *invert {
+                    state.invert();
+                }
+                if state.false_at.is_none() {
+                    state.set_registers(*base_address, *pointer);
+                }
+            }
+            Instruction::Terminator {
+                base_address,
+                pointer,
+            } => {
+                state.depth = 0;
+                state.false_at = None;
+                state.set_registers(*base_address, *pointer);
+            }
+            Instruction::End => break,
+            _ if !active => {}
+            Instruction::Write {
+                address,
+                size,
+                value,
+                count,
+            } => {
+                let len = match size {
+                    Size::U8 => 1,
+                    Size::U16 => 2,
+                    Size::U32 => 4,
+                };
+                write_sized(interface, state.address(*address), len, *value, *count)?;
+            }
+            Instruction::WriteBytes { address, bytes } => {
+                interface.write_bytes(state.address(*address), bytes)?;
+            }
+            Instruction::Load {
+                register,
+                add,
+                address,
+                offset_by,
+            } => {
+                let addr = address.wrapping_add(offset_by.map_or(0, |r| state.register(r)));
+                let loaded = read_u32(interface, addr)?;
+                let target = state.register_mut(*register);
+                *target = if *add {
+                    target.wrapping_add(loaded)
+                } else {
+                    loaded
+                };
+            }
+            Instruction::Set {
+                register,
+                add,
+                value,
+                offset_by,
+            } => {
+                let value = value.wrapping_add(offset_by.map_or(0, |r| state.register(r)));
+                let target = state.register_mut(*register);
+                *target = if *add {
+                    target.wrapping_add(value)
+                } else {
+                    value
+                };
+            }
+        }
+    }
+    Ok(())
+}
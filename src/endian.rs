@@ -1,22 +1,89 @@
-use crate::game_state::{GameMode, GameOstrich, GameState};
+//! Conversion of values between the game's byte order and the host's.
+//!
+//! The GameCube is big-endian, so values read from the game's memory must have their bytes swapped before they can
+//! be used on a little-endian host. [`EndianAware`] describes how to do this for a type, field by field, and can be
+//! derived for `#[repr(C)]` structs.
+//!
+//! # Examples
+//! ```
+//! use bfbb::endian::EndianAware;
+//! use bytemuck::{Pod, Zeroable};
+//!
+//! /// The game's `_xCounter` struct.
+//! #[derive(Clone, Copy, Pod, Zeroable, EndianAware)]
+//! #[repr(C)]
+//! struct Counter {
+//!     id: u32,
+//!     base_type: u8,
+//!     link_count: u8,
+//!     base_flags: u16,
+//!     links: u32,
+//!     event_func: u32,
+//!     asset: u32,
+//!     state: u8,
+//!     counter_flags: u8,
+//!     count: i16,
+//! }
+//!
+//! let mut bytes = [0u8; std::mem::size_of::<Counter>()];
+//! bytes[0x14..0x18].copy_from_slice(&[0x00, 0x01, 0x00, 0x02]);
+//! Counter::swap_endian(&mut bytes);
+//!
+//! let counter: Counter = bytemuck::cast(bytes);
+//! if cfg!(target_endian = "little") {
+//!     assert_eq!((counter.state, counter.counter_flags, counter.count), (0, 1, 2));
+//! }
+//! ```
 
+use crate::{
+    game_state::{GameMode, GameOstrich, GameState},
+    SceneId,
+};
+
+/// Derive [`EndianAware`] for a `#[repr(C)]` struct whose fields are all [`EndianAware`].
+pub use bfbb_derive::EndianAware;
+
+/// A type whose in-memory representation can be converted between the game's byte order and the host's.
 pub trait EndianAware {
-    const NEEDS_SWAP: bool;
+    /// Convert `bytes`, the in-memory representation of a `Self`, between the game's (big-endian) byte order and the
+    /// host's byte order. This conversion is its own inverse.
+    ///
+    /// `bytes` is always exactly `size_of::<Self>()` bytes long.
+    fn swap_endian(bytes: &mut [u8]);
 }
 
 macro_rules! impl_aware {
-    ( false: $( $f:ty ),*; true: $( $t:ty ),* )=> {
-        $( impl_aware!(false, $f); )*
-        $( impl_aware!(true,  $t); )*
-    };
-    ( $needs_swap:expr, $typ:ty) => {
-        impl EndianAware for $typ {
-            const NEEDS_SWAP: bool = $needs_swap;
-        }
+    ( single: $( $s:ty ),*; multi: $( $m:ty ),* )=> {
+        $(
+            impl EndianAware for $s {
+                fn swap_endian(_: &mut [u8]) {}
+            }
+        )*
+        $(
+            impl EndianAware for $m {
+                fn swap_endian(bytes: &mut [u8]) {
+                    if cfg!(target_endian = "little") {
+                        bytes.reverse();
+                    }
+                }
+            }
+        )*
     };
 }
 
-impl_aware!(false: bool, u8, GameMode, GameState, GameOstrich; true: i16, u32);
-impl<const N: usize> EndianAware for [u8; N] {
-    const NEEDS_SWAP: bool = false;
+impl_aware!(
+    single: bool, u8, i8, GameMode, GameState, GameOstrich, SceneId;
+    multi: i16, u16, i32, u32, i64, u64, f32, f64
+);
+
+/// Each element of an array is swapped individually, so `[u32; N]` or `[f32; 3]` are read correctly.
+impl<T: EndianAware, const N: usize> EndianAware for [T; N] {
+    fn swap_endian(bytes: &mut [u8]) {
+        if std::mem::size_of::<T>() == 0 {
+            return;
+        }
+        for element in bytes.chunks_exact_mut(std::mem::size_of::<T>()) {
+            T::swap_endian(element);
+        }
+    }
 }
@@ -0,0 +1,295 @@
+//! Detecting which build of the game is running.
+//!
+//! The addresses used by a [`GameInterface`] are only correct for the build of the game they were found in. The
+//! disc header at the start of memory identifies the game and its revision, but ROM hacks, demos and modified
+//! executables can share a header with the retail game. A [`KnownBuild`] therefore lists [`RegionHash`]es of the
+//! loaded executable, which must all match for the build to be known. A build matched by its disc header alone is
+//! not known.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::build::{self, KnownBuild, RegionHash};
+//! use bfbb::game_interface::mock::MockInterface;
+//! use bfbb::game_interface::{InterfaceError, InterfaceResult};
+//!
+//! # fn main() -> InterfaceResult<()> {
+//! let mut interface = MockInterface::default();
+//! interface.write_bytes(0x8000_0000, b"GQPE78\0\0")?;
+//! interface.write_bytes(0x8000_3100, &[0x3C, 0x20, 0x80, 0x3D])?;
+//!
+//! // Record a fingerprint of the running executable
+//! let hash = RegionHash::compute(&interface, 0x8000_3100, 4)?;
+//! let builds = [KnownBuild {
+//!     name: "Retail".into(),
+//!     game_code: *b"GQPE78",
+//!     revision: 0,
+//!     hashes: vec![hash],
+//! }];
+//! let detected = build::detect(&interface, &builds)?;
+//! assert_eq!(detected.name(), Some("Retail"));
+//! assert!(detected.is_known());
+//!
+//! // A modified executable is not recognized
+//! interface.write_bytes(0x8000_3100, &[0x60, 0, 0, 0])?;
+//! assert!(!build::detect(&interface, &builds)?.is_known());
+//!
+//! // So is a build without any fingerprints
+//! let header_only = [KnownBuild {
+//!     hashes: Vec::new(),
+//!     ..builds[0].clone()
+//! }];
+//! let detected = build::detect(&interface, &header_only)?;
+//! assert_eq!(detected.name(), Some("Retail"));
+//! assert!(detected.is_header_only());
+//! assert!(!detected.is_known());
+//!
+//! // Another publisher's game is refused, even if its game code starts the same way
+//! interface.write_bytes(0x8000_0000, b"GQPE01")?;
+//! assert!(matches!(
+//!     build::detect(&interface, &builds),
+//!     Err(InterfaceError::IncorrectGame)
+//! ));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::save::gci::{GAME_CODE_PREFIX, MAKER_CODE};
+
+use super::{game_var::InterfaceBackend, GameInterface, InterfaceError, InterfaceResult};
+
+/// Address of the game code (e.g. `GQPE78`) in the disc header.
+const GAME_CODE_ADDRESS: u32 = 0x8000_0000;
+
+/// A build of the game that this crate's addresses are known to be correct for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownBuild {
+    /// A human-readable name for this build.
+    pub name: String,
+    /// The game code from the disc header (e.g. `GQPE78`).
+    pub game_code: [u8; 6],
+    /// The revision from the disc header.
+    pub revision: u8,
+    /// Hashes of regions of the loaded executable, which must all match. If empty, only the disc header is checked
+    /// and a matching build is not [known](Build::is_known).
+    pub hashes: Vec<RegionHash>,
+}
+
+/// A hash of a region of the game's memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionHash {
+    /// The address of the start of the region.
+    pub addr: u32,
+    /// The length of the region in bytes.
+    pub len: u32,
+    /// The 64-bit FNV-1a hash of the region.
+    pub hash: u64,
+}
+
+/// The build of the game detected by [`detect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Build {
+    /// The game code from the disc header.
+    pub game_code: [u8; 6],
+    /// The disc number from the disc header.
+    pub disc: u8,
+    /// The revision from the disc header.
+    pub revision: u8,
+    known: Option<KnownBuild>,
+}
+
+/// The builds of the game this crate's addresses are known to be correct for.
+///
+/// **NOTE:** No fingerprints of the retail executable have been recorded yet, so it is only matched by its disc header
+/// and [`Build::is_known`] is `false`. A [`DolphinInterface`](super::dolphin::DolphinInterface) therefore refuses to
+/// hook it unless [allowed](super::dolphin::DolphinInterface::allow_unknown_builds), or given a table created with
+/// [`KnownBuild::record`] on a known-good copy of the game.
+#[must_use]
+pub fn known_builds() -> Vec<KnownBuild> {
+    vec![KnownBuild {
+        name: "NTSC-U".into(),
+        game_code: *b"GQPE78",
+        revision: 0,
+        hashes: Vec::new(),
+    }]
+}
+
+/// Detect which of `builds` is running in the game accessed by `interface`.
+///
+/// A build is recognized if the disc header matches and every one of its [`RegionHash`]es matches. The returned
+/// [`Build`] is unknown if none of `builds` is recognized, or if the recognized build has no hashes.
+///
+/// # Errors
+///
+/// Will return an [`InterfaceError::IncorrectGame`] if the running game is not a release of BfBB, or another
+/// [`InterfaceError`] if the implementation is unable to access the game.
+pub fn detect<F: InterfaceBackend>(
+    interface: &GameInterface<F>,
+    builds: &[KnownBuild],
+) -> InterfaceResult<Build> {
+    let (game_code, header) = read_header(interface)?;
+
+    let mut known = None;
+    for build in builds
+        .iter()
+        .filter(|b| b.game_code == game_code && b.revision == header[7])
+    {
+        if build.matches(interface)? {
+            known = Some(build.clone());
+            break;
+        }
+    }
+
+    let build = Build {
+        game_code,
+        disc: header[6],
+        revision: header[7],
+        known,
+    };
+    match &build.known {
+        Some(known) if known.hashes.is_empty() => log::warn!(
+            "Matched {} by its disc header only, the executable was not verified",
+            known.name
+        ),
+        Some(known) => log::debug!("Detected {}", known.name),
+        None => log::debug!("Unknown build: {build}"),
+    }
+    Ok(build)
+}
+
+impl KnownBuild {
+    /// Fingerprint the build of the game accessed by `interface`, hashing each of `regions` as an address and a
+    /// length. The game code and revision are read from the disc header.
+    ///
+    /// The game should be a known-good copy, and the regions should cover its executable code, such as the `.init`
+    /// and `.text` sections.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::build::{self, KnownBuild};
+    /// use bfbb::game_interface::mock::MockInterface;
+    /// use bfbb::game_interface::InterfaceResult;
+    ///
+    /// # fn main() -> InterfaceResult<()> {
+    /// let mut interface = MockInterface::default();
+    /// interface.write_bytes(0x8000_0000, b"GQPE78\0\0")?;
+    /// interface.write_bytes(0x8000_3100, &[0x3C, 0x20, 0x80, 0x3D])?;
+    ///
+    /// // With a real game, use the `.init` and `.text` sections from the header of its executable
+    /// let known = KnownBuild::record("Retail", &interface, &[(0x8000_3100, 4)])?;
+    /// assert_eq!(known.game_code, *b"GQPE78");
+    /// assert_eq!(known.hashes.len(), 1);
+    ///
+    /// let detected = build::detect(&interface, &[known])?;
+    /// assert!(detected.is_known());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError::IncorrectGame`] if the running game is not a release of BfBB, or another
+    /// [`InterfaceError`] if the implementation is unable to access the game.
+    pub fn record<F: InterfaceBackend>(
+        name: impl Into<String>,
+        interface: &GameInterface<F>,
+        regions: &[(u32, u32)],
+    ) -> InterfaceResult<Self> {
+        let (game_code, header) = read_header(interface)?;
+        let hashes = regions
+            .iter()
+            .map(|&(addr, len)| RegionHash::compute(interface, addr, len))
+            .collect::<InterfaceResult<_>>()?;
+        Ok(Self {
+            name: name.into(),
+            game_code,
+            revision: header[7],
+            hashes,
+        })
+    }
+
+    fn matches<F: InterfaceBackend>(&self, interface: &GameInterface<F>) -> InterfaceResult<bool> {
+        for expected in &self.hashes {
+            if RegionHash::compute(interface, expected.addr, expected.len)? != *expected {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl RegionHash {
+    /// Hash `len` bytes of the game's memory starting at `addr`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`] if the implementation is unable to access the game.
+    pub fn compute<F: InterfaceBackend>(
+        interface: &GameInterface<F>,
+        addr: u32,
+        len: u32,
+    ) -> InterfaceResult<Self> {
+        let mut bytes = vec![0; len as usize];
+        interface.read_bytes(addr, &mut bytes)?;
+        Ok(Self {
+            addr,
+            len,
+            hash: fnv1a(&bytes),
+        })
+    }
+}
+
+impl Build {
+    /// Returns `true` if this build was recognized as one of the known builds by hashes of its executable.
+    #[must_use]
+    pub fn is_known(&self) -> bool {
+        self.known.as_ref().map_or(false, |b| !b.hashes.is_empty())
+    }
+
+    /// Returns `true` if this build was matched to a known build without any hashes, so only its disc header was
+    /// checked. Such a build is not [known](Self::is_known).
+    #[must_use]
+    pub fn is_header_only(&self) -> bool {
+        self.known.as_ref().map_or(false, |b| b.hashes.is_empty())
+    }
+
+    /// The name of the matched build, even if it was only matched by its disc header.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.known.as_ref().map(|b| b.name.as_str())
+    }
+
+    /// The game code as a string, such as `"GQPE78"`.
+    #[must_use]
+    pub fn game_code(&self) -> String {
+        String::from_utf8_lossy(&self.game_code).into_owned()
+    }
+}
+
+/// Formats a build as its game code and revision, such as `GQPE78 rev 0`.
+impl std::fmt::Display for Build {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} rev {}", self.game_code(), self.revision)
+    }
+}
+
+/// Read the disc header, checking that its game and maker code are a release of BfBB.
+fn read_header<F: InterfaceBackend>(
+    interface: &GameInterface<F>,
+) -> InterfaceResult<([u8; 6], [u8; 8])> {
+    let mut header = [0; 8];
+    interface.read_bytes(GAME_CODE_ADDRESS, &mut header)?;
+    let mut game_code = [0; 6];
+    game_code.copy_from_slice(&header[..6]);
+    if !game_code.starts_with(GAME_CODE_PREFIX) || &game_code[4..] != MAKER_CODE {
+        log::error!("Incorrect game code: {game_code:?}");
+        return Err(InterfaceError::IncorrectGame);
+    }
+    Ok((game_code, header))
+}
+
+/// The 64-bit FNV-1a hash of `bytes`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
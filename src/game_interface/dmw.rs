@@ -0,0 +1,338 @@
+//! Reading and writing Dolphin Memory Engine watch lists.
+//!
+//! [Dolphin Memory Engine](https://github.com/aldelaro5/Dolphin-memory-engine) saves its watches as `.dmw` files, a
+//! JSON tree of groups and watches. Each watch has a label, a type and an address, optionally followed by the offsets
+//! of a pointer-path. A [`WatchList`] can be loaded into [`DynVar`]s for any backend, and the variables known to this
+//! crate can be exported with [`WatchList::known_variables`] for debugging in Dolphin Memory Engine.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::dmw::WatchList;
+//! use bfbb::game_interface::dynamic::{DynType, DynValue};
+//! use bfbb::game_interface::game_var::{GameVar, GameVarMut};
+//! use bfbb::game_interface::mock::MockInterface;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let json = r#"{
+//!   "watchList": [
+//!     {
+//!       "groupName": "Counters",
+//!       "groupEntries": [
+//!         { "label": "Spatulas", "address": "803C205C", "typeIndex": 2, "unsigned": true, "baseIndex": 0 }
+//!       ]
+//!     }
+//!   ]
+//! }"#;
+//! let list = WatchList::from_json(json)?;
+//!
+//! let mut interface = MockInterface::default();
+//! interface.write_bytes(0x803C_205C, &9u32.to_be_bytes())?;
+//! let mut vars = list.vars(&interface);
+//! assert_eq!(vars[0].0, "Counters/Spatulas");
+//! assert_eq!(vars[0].1.path(), &[0x803C_205C]);
+//! assert_eq!(vars[0].1.ty(), DynType::U32);
+//! assert_eq!(vars[0].1.get()?, DynValue::U32(9));
+//!
+//! vars[0].1.set(DynValue::U32(75))?;
+//! let mut bytes = [0; 4];
+//! interface.read_bytes(0x803C_205C, &mut bytes)?;
+//! assert_eq!(bytes, 75u32.to_be_bytes());
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::Spatula;
+
+use super::{
+    dolphin::dolphin_var::{
+        task_entity_path, task_menu_count_path, GAME_MODE_ADDRESS, GAME_OSTRICH_ADDRESS,
+        GAME_STATE_ADDRESS, HANS_ADDRESS, LAB_DOOR_ADDRESS, LOADING_ADDRESS, POWERS_ADDRESS,
+        SCENE_PTR_ADDRESS, SHINY_COUNT_ADDRESS, SOCK_COUNT_ADDRESS, SPATULA_COUNT_ADDRESS,
+        TASK_FLAGS_OFFSET, TASK_STATE_OFFSET,
+    },
+    dynamic::{DynType, DynVar},
+    game_var::InterfaceBackend,
+    GameInterface,
+};
+
+/// Result type for reading watch lists.
+pub type DmwResult<T> = std::result::Result<T, DmwError>;
+
+/// Error type for failures to read a watch list.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DmwError {
+    /// The file is not valid JSON or is not a watch list.
+    #[error("Invalid watch list")]
+    Json(#[from] serde_json::Error),
+    /// A watch has a type that is not supported.
+    #[error("Watch '{0}' has an unsupported type")]
+    UnsupportedType(String),
+    /// A watch's address or one of its offsets is not a hexadecimal number.
+    #[error("Watch '{0}' has an invalid address")]
+    InvalidAddress(String),
+}
+
+/// A tree of watches, as saved by Dolphin Memory Engine.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchList {
+    /// The top-level groups and watches.
+    pub nodes: Vec<WatchNode>,
+}
+
+/// A group or a watch within a [`WatchList`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchNode {
+    /// A named group of other nodes.
+    Group {
+        /// The name of the group.
+        name: String,
+        /// The groups and watches within the group.
+        nodes: Vec<WatchNode>,
+    },
+    /// A single watch.
+    Watch(Watch),
+}
+
+/// A labelled variable within a [`WatchList`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watch {
+    /// The label of the watch.
+    pub label: String,
+    /// The pointer-path of the variable, in the same form as
+    /// [`InterfaceBackend::create_var`](super::game_var::InterfaceBackend::create_var).
+    pub path: Vec<usize>,
+    /// The type of the variable.
+    pub ty: DynType,
+}
+
+fn watch(label: impl Into<String>, path: impl Into<Vec<usize>>, ty: DynType) -> WatchNode {
+    WatchNode::Watch(Watch {
+        label: label.into(),
+        path: path.into(),
+        ty,
+    })
+}
+
+impl WatchList {
+    /// Read a watch list from the contents of a `.dmw` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`DmwError`] if `json` is not a valid watch list, or it contains a watch of an unsupported type.
+    pub fn from_json(json: &str) -> DmwResult<Self> {
+        let file: RawFile = serde_json::from_str(json)?;
+        Ok(Self {
+            nodes: file
+                .watch_list
+                .into_iter()
+                .map(RawNode::into_node)
+                .collect::<DmwResult<_>>()?,
+        })
+    }
+
+    /// Write this watch list in the format of a `.dmw` file.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let file = RawFile {
+            watch_list: self.nodes.iter().map(RawNode::from_node).collect(),
+        };
+        serde_json::to_string_pretty(&file).expect("Watch lists should always be serializable")
+    }
+
+    /// Every watch in this list, depth-first, named by its label prefixed with the names of its groups, separated by
+    /// `/`.
+    #[must_use]
+    pub fn watches(&self) -> Vec<(String, &Watch)> {
+        fn visit<'a>(nodes: &'a [WatchNode], prefix: &str, out: &mut Vec<(String, &'a Watch)>) {
+            for node in nodes {
+                match node {
+                    WatchNode::Group { name, nodes } => {
+                        visit(nodes, &format!("{prefix}{name}/"), out)
+                    }
+                    WatchNode::Watch(w) => out.push((format!("{prefix}{}", w.label), w)),
+                }
+            }
+        }
+        let mut out = Vec::new();
+        visit(&self.nodes, "", &mut out);
+        out
+    }
+
+    /// Create a [`DynVar`] for every watch in this list, named as in [`watches`](Self::watches).
+    pub fn vars<F: InterfaceBackend>(
+        &self,
+        interface: &GameInterface<F>,
+    ) -> Vec<(String, DynVar<F>)> {
+        self.watches()
+            .into_iter()
+            .filter(|(_, w)| !w.path.is_empty())
+            .map(|(name, w)| (name, interface.dyn_var(w.path.clone(), w.ty)))
+            .collect()
+    }
+
+    /// A watch list of the variables known to this crate, such as those of a [`GameInterface`] and its [`Tasks`](super::Tasks).
+    #[must_use]
+    pub fn known_variables() -> Self {
+        let powers = WatchNode::Group {
+            name: "Powers".into(),
+            nodes: vec![
+                watch("Bubble bowl", [POWERS_ADDRESS], DynType::U8),
+                watch("Cruise bubble", [POWERS_ADDRESS + 1], DynType::U8),
+                watch("Initial bubble bowl", [POWERS_ADDRESS + 2], DynType::U8),
+                watch("Initial cruise bubble", [POWERS_ADDRESS + 3], DynType::U8),
+            ],
+        };
+        let tasks = WatchNode::Group {
+            name: "Tasks".into(),
+            nodes: Spatula::iter()
+                .map(|s| {
+                    let mut nodes =
+                        vec![watch("Menu count", task_menu_count_path(s), DynType::I16)];
+                    if let Some(path) = task_entity_path(s, TASK_FLAGS_OFFSET) {
+                        nodes.push(watch("Flags", path, DynType::U8));
+                    }
+                    if let Some(path) = task_entity_path(s, TASK_STATE_OFFSET) {
+                        nodes.push(watch("State", path, DynType::U32));
+                    }
+                    WatchNode::Group {
+                        name: s.to_string(),
+                        nodes,
+                    }
+                })
+                .collect(),
+        };
+
+        Self {
+            nodes: vec![
+                watch("Is loading", [LOADING_ADDRESS], DynType::U8),
+                watch("Game state", [GAME_STATE_ADDRESS], DynType::U8),
+                watch("Game mode", [GAME_MODE_ADDRESS], DynType::U8),
+                watch("Game ostrich", [GAME_OSTRICH_ADDRESS], DynType::U8),
+                watch("Scene id", [SCENE_PTR_ADDRESS, 0], DynType::String(4)),
+                watch("Spatula count", [SPATULA_COUNT_ADDRESS], DynType::U32),
+                watch("Shiny count", [SHINY_COUNT_ADDRESS], DynType::U32),
+                watch("Sock count", [SOCK_COUNT_ADDRESS], DynType::U32),
+                watch("Lab door cost", [LAB_DOOR_ADDRESS], DynType::U32),
+                watch("Hans flags", [HANS_ADDRESS], DynType::U8),
+                powers,
+                tasks,
+            ],
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawFile {
+    #[serde(rename = "watchList")]
+    watch_list: Vec<RawNode>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum RawNode {
+    Group {
+        #[serde(rename = "groupName")]
+        group_name: String,
+        #[serde(rename = "groupEntries", default)]
+        group_entries: Vec<RawNode>,
+    },
+    Watch(RawWatch),
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RawWatch {
+    label: String,
+    address: String,
+    type_index: u32,
+    #[serde(default)]
+    unsigned: bool,
+    #[serde(default)]
+    base_index: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pointer_offsets: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    length: Option<usize>,
+}
+
+impl RawNode {
+    fn into_node(self) -> DmwResult<WatchNode> {
+        match self {
+            Self::Group {
+                group_name,
+                group_entries,
+            } => Ok(WatchNode::Group {
+                name: group_name,
+                nodes: group_entries
+                    .into_iter()
+                    .map(Self::into_node)
+                    .collect::<DmwResult<_>>()?,
+            }),
+            Self::Watch(w) => {
+                let parse_hex = |s: &String| {
+                    let s = s.trim();
+                    let s = s.strip_prefix("0x").unwrap_or(s);
+                    usize::from_str_radix(s, 16)
+                        .map_err(|_| DmwError::InvalidAddress(w.label.clone()))
+                };
+                let path = std::iter::once(&w.address)
+                    .chain(&w.pointer_offsets)
+                    .map(parse_hex)
+                    .collect::<DmwResult<Vec<_>>>()?;
+                let length = w.length.unwrap_or(1);
+                let ty = match (w.type_index, w.unsigned) {
+                    (0, true) => DynType::U8,
+                    (0, false) => DynType::I8,
+                    (1, true) => DynType::U16,
+                    (1, false) => DynType::I16,
+                    (2, true) => DynType::U32,
+                    (2, false) => DynType::I32,
+                    (3, _) => DynType::F32,
+                    (4, _) => DynType::F64,
+                    (5, _) => DynType::String(length),
+                    (6, _) => DynType::Bytes(length),
+                    _ => return Err(DmwError::UnsupportedType(w.label)),
+                };
+                Ok(watch(w.label, path, ty))
+            }
+        }
+    }
+
+    fn from_node(node: &WatchNode) -> Self {
+        match node {
+            WatchNode::Group { name, nodes } => Self::Group {
+                group_name: name.clone(),
+                group_entries: nodes.iter().map(Self::from_node).collect(),
+            },
+            WatchNode::Watch(w) => {
+                let (type_index, unsigned, length) = match w.ty {
+                    DynType::U8 => (0, true, None),
+                    DynType::I8 => (0, false, None),
+                    DynType::U16 => (1, true, None),
+                    DynType::I16 => (1, false, None),
+                    DynType::U32 => (2, true, None),
+                    DynType::I32 => (2, false, None),
+                    DynType::F32 => (3, false, None),
+                    DynType::F64 => (4, false, None),
+                    DynType::String(len) => (5, false, Some(len)),
+                    DynType::Bytes(len) => (6, false, Some(len)),
+                };
+                let mut path = w.path.iter().map(|x| format!("{x:X}"));
+                Self::Watch(RawWatch {
+                    label: w.label.clone(),
+                    address: path.next().unwrap_or_default(),
+                    type_index,
+                    unsigned,
+                    base_index: 0,
+                    pointer_offsets: path.collect(),
+                    length,
+                })
+            }
+        }
+    }
+}
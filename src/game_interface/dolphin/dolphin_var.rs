@@ -1,30 +1,70 @@
 //! Implementation of Dolphin backend for [`GameInterface`]
 
+use std::marker::PhantomData;
+
 use bytemuck::CheckedBitPattern;
-use process_memory::{Memory, ProcessHandle};
+use process_memory::{CopyAddress, ProcessHandle, PutAddress};
 use strum::IntoEnumIterator;
 
 use crate::{
     endian::EndianAware,
     game_interface::{
+        entity::Entities,
         game_var::{GameVar, GameVarMut, InterfaceBackend},
+        patch::PatchRegistry,
+        ptr::{self, MEMORY_START},
         GameInterface, Hans, InterfaceResult, PowerUps, Task, Tasks,
     },
     Spatula,
 };
 
-use super::data_member::{CheckedMemory, DataMember};
-
 /// Dolphin implementation for [`InterfaceBackend`]
 pub enum DolphinBackend {}
 impl InterfaceBackend for DolphinBackend {
     type Var<T: CheckedBitPattern + EndianAware> = DolphinVar<T>;
     type Mut<T: CheckedBitPattern + EndianAware> = DolphinVar<T>;
+    type Handle = DolphinHandle;
+
+    fn create_var<T: CheckedBitPattern + EndianAware>(
+        handle: &Self::Handle,
+        path: &[usize],
+    ) -> Self::Mut<T> {
+        DolphinVar::new(path, handle.base_addr, handle.handle)
+    }
+
+    fn read_bytes(handle: &Self::Handle, addr: u32, buf: &mut [u8]) -> InterfaceResult<()> {
+        let offset = handle.offset_of(addr, buf.len())?;
+        Ok(handle.handle.copy_address(offset, buf)?)
+    }
+
+    fn write_bytes(handle: &Self::Handle, addr: u32, bytes: &[u8]) -> InterfaceResult<()> {
+        let offset = handle.offset_of(addr, bytes.len())?;
+        Ok(handle.handle.put_address(offset, bytes)?)
+    }
+}
+
+/// Dolphin implementation for [`InterfaceBackend::Handle`]
+///
+/// Locates the emulated GameCube memory within Dolphin's process.
+#[derive(Clone, Copy)]
+pub struct DolphinHandle {
+    base_addr: usize,
+    handle: ProcessHandle,
+}
+
+impl DolphinHandle {
+    /// Get the address within Dolphin's process of the `len` bytes at the GameCube virtual address `addr`.
+    fn offset_of(&self, addr: u32, len: usize) -> InterfaceResult<usize> {
+        ptr::check_range(addr, len)?;
+        Ok(self.base_addr + (addr - MEMORY_START) as usize)
+    }
 }
 
 /// Dolphin implementation for [`GameVar`] and [`GameVarMut`]
 pub struct DolphinVar<T> {
-    ptr: DataMember<T>,
+    path: Vec<usize>,
+    handle: DolphinHandle,
+    _target: PhantomData<T>,
 }
 
 impl<T> DolphinVar<T> {
@@ -35,13 +75,36 @@ impl<T> DolphinVar<T> {
     /// may feasibly be useful more broadly.
     ///
     /// Since this takes an arbitrary pointer-path and the game's memory is backed by regular system memory owned by Dolphin,
-    /// you can technically use this to modify more than just game data. For example, you could theoretically patch/re-write
-    /// instructions in the game's executable code, though this use-case is currently not prioritized and may pose other issues,
-    /// such as not having any method to ensure a patch is made before the game starts executing (Consider if a Dolphin AR code will work for
+    /// you can technically use this to modify more than just game data. To patch instructions in the game's executable code,
+    /// use a [`PatchManager`](crate::game_interface::patch::PatchManager) instead, which verifies and reverts patches. There is
+    /// still no method to ensure a patch is made before the game starts executing (Consider if a Dolphin AR code will work for
     /// your use-case instead.).
+    ///
+    /// `T` may be any [`EndianAware`] type, including primitives such as `f32` and `u64`, arrays such as `[f32; 3]`, or
+    /// structs deriving [`EndianAware`](crate::endian::EndianAware).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bfbb::game_interface::dolphin::dolphin_var::DolphinVar;
+    /// use bfbb::game_interface::game_var::GameVar;
+    /// use bfbb::game_interface::InterfaceResult;
+    /// use process_memory::ProcessHandle;
+    ///
+    /// /// Read an `xVec3` located `offset` bytes into the struct pointed to by `ptr_addr`.
+    /// fn read_vec3(
+    ///     ptr_addr: usize,
+    ///     offset: usize,
+    ///     base_addr: usize,
+    ///     handle: ProcessHandle,
+    /// ) -> InterfaceResult<[f32; 3]> {
+    ///     DolphinVar::<[f32; 3]>::new([ptr_addr, offset], base_addr, handle).get()
+    /// }
+    /// ```
     pub fn new(addr: impl Into<Vec<usize>>, base_addr: usize, handle: ProcessHandle) -> Self {
         Self {
-            ptr: DataMember::new_offset(handle, base_addr, addr.into()),
+            path: addr.into(),
+            handle: DolphinHandle { base_addr, handle },
+            _target: PhantomData,
         }
     }
 }
@@ -49,22 +112,30 @@ impl<T> DolphinVar<T> {
 impl<T: EndianAware + CheckedBitPattern> GameVar for DolphinVar<T> {
     type Target = T;
     fn get(&self) -> InterfaceResult<T> {
-        Ok(self.ptr.checked_read()?)
+        ptr::read_value::<DolphinBackend, T>(&self.handle, &self.path)
     }
 }
 impl<T: EndianAware + CheckedBitPattern> GameVarMut for DolphinVar<T> {
     fn set(&mut self, value: T) -> InterfaceResult<()> {
-        Ok(self.ptr.write(&value)?)
+        ptr::write_value::<DolphinBackend, T>(&self.handle, &self.path, &value)
     }
 }
 
-const LOADING_ADDRESS: usize = 0x803C_B7B3;
-const GAME_STATE_ADDRESS: usize = 0x803C_AB43;
-const GAME_MODE_ADDRESS: usize = 0x803C_B8AB;
-const GAME_OSTRICH_ADDRESS: usize = 0x803C_B8AF;
-const SCENE_PTR_ADDRESS: usize = 0x803C_2518;
-const SPATULA_COUNT_ADDRESS: usize = 0x803C_205C;
-const LAB_DOOR_ADDRESS: usize = 0x804F_6CB8;
+pub(crate) const LOADING_ADDRESS: usize = 0x803C_B7B3;
+pub(crate) const GAME_STATE_ADDRESS: usize = 0x803C_AB43;
+pub(crate) const GAME_MODE_ADDRESS: usize = 0x803C_B8AB;
+pub(crate) const GAME_OSTRICH_ADDRESS: usize = 0x803C_B8AF;
+pub(crate) const SCENE_PTR_ADDRESS: usize = 0x803C_2518;
+
+// The inventory counters are fields of `globals.player`, a `zPlayerGlobals` declared in the decomp's `zPlayer.h`.
+/// `Inv_Shiny`, the word before `Inv_Spatula`.
+pub(crate) const SHINY_COUNT_ADDRESS: usize = 0x803C_2058;
+/// `Inv_Spatula`.
+pub(crate) const SPATULA_COUNT_ADDRESS: usize = 0x803C_205C;
+/// `Inv_PatsSock_Total`, `0xC0` bytes after `Inv_Spatula`. It follows the per-level `Inv_PatsSock`,
+/// `Inv_PatsSock_Max` and `Inv_LevelPickups` arrays of 15 words each and their `_CurrentLevel` counts.
+pub(crate) const SOCK_COUNT_ADDRESS: usize = 0x803C_211C;
+pub(crate) const LAB_DOOR_ADDRESS: usize = 0x804F_6CB8;
 
 impl GameInterface<DolphinBackend> {
     pub(crate) fn new(base_addr: usize, handle: ProcessHandle) -> Self {
@@ -77,37 +148,64 @@ impl GameInterface<DolphinBackend> {
             powers: PowerUps::new(base_addr, handle),
             scene_id: DolphinVar::new([SCENE_PTR_ADDRESS, 0], base_addr, handle),
             spatula_count: DolphinVar::new([SPATULA_COUNT_ADDRESS], base_addr, handle),
+            shiny_count: DolphinVar::new([SHINY_COUNT_ADDRESS], base_addr, handle),
+            sock_count: DolphinVar::new([SOCK_COUNT_ADDRESS], base_addr, handle),
             tasks: Tasks::new(base_addr, handle),
+            entities: Entities::new(base_addr, handle),
             lab_door_cost: DolphinVar::new([LAB_DOOR_ADDRESS], base_addr, handle),
+            handle: DolphinHandle { base_addr, handle },
+            patches: PatchRegistry::default(),
+        }
+    }
+}
+
+impl Entities<DolphinBackend> {
+    fn new(base_addr: usize, handle: ProcessHandle) -> Self {
+        Self {
+            count: DolphinVar::new([SCENE_PTR_ADDRESS, 0x74], base_addr, handle),
+            array: vec![SCENE_PTR_ADDRESS, 0x78],
+            handle: DolphinHandle { base_addr, handle },
         }
     }
 }
 
 const SWORLD_BASE: usize = 0x802F_63C8;
+
+/// Path to the count of the `_xCounter` for `spatula`'s task in the pause menu.
+pub(crate) fn task_menu_count_path(spatula: Spatula) -> [usize; 2] {
+    const SIZE_OF_MENU_WORLD: usize = 0x24C;
+    const SIZE_OF_MENU_TASK: usize = 0x48;
+
+    // Calcuate address of the _xCounter for this task in the menu
+    let (world, idx) = spatula.into();
+    let counter_addr =
+        SWORLD_BASE + world * SIZE_OF_MENU_WORLD + 0xC + idx * SIZE_OF_MENU_TASK + 0x14;
+    [counter_addr, 0x14]
+}
+
+/// Offset of the flags of a spatula's entity.
+pub(crate) const TASK_FLAGS_OFFSET: usize = 0x18;
+/// Offset of the model state of a spatula's entity.
+pub(crate) const TASK_STATE_OFFSET: usize = 0x16C;
+
+/// Path to the field at `field_offset` within `spatula`'s entity, if it has one.
+pub(crate) fn task_entity_path(spatula: Spatula, field_offset: usize) -> Option<[usize; 4]> {
+    let offset = spatula.get_offset()? * std::mem::size_of::<u32>();
+    Some([SCENE_PTR_ADDRESS, 0x78, offset, field_offset])
+}
+
 impl Tasks<DolphinBackend> {
     fn new(base_addr: usize, handle: ProcessHandle) -> Self {
-        const SIZE_OF_MENU_WORLD: usize = 0x24C;
-        const SIZE_OF_MENU_TASK: usize = 0x48;
-
         let arr = Spatula::iter()
             .map(|s| {
-                // Calcuate address of the _xCounter for this task in the menu
-                let (world, idx) = s.into();
-                let counter_addr =
-                    SWORLD_BASE + world * SIZE_OF_MENU_WORLD + 0xC + idx * SIZE_OF_MENU_TASK + 0x14;
-
-                let offset = s.get_offset().map(|x| x * std::mem::size_of::<u32>());
-
                 (
                     s,
                     Task {
-                        menu_count: DolphinVar::new([counter_addr, 0x14], base_addr, handle),
-                        flags: offset.map(|x| {
-                            DolphinVar::new([SCENE_PTR_ADDRESS, 0x78, x, 0x18], base_addr, handle)
-                        }),
-                        state: offset.map(|x| {
-                            DolphinVar::new([SCENE_PTR_ADDRESS, 0x78, x, 0x16C], base_addr, handle)
-                        }),
+                        menu_count: DolphinVar::new(task_menu_count_path(s), base_addr, handle),
+                        flags: task_entity_path(s, TASK_FLAGS_OFFSET)
+                            .map(|path| DolphinVar::new(path, base_addr, handle)),
+                        state: task_entity_path(s, TASK_STATE_OFFSET)
+                            .map(|path| DolphinVar::new(path, base_addr, handle)),
                     },
                 )
             })
@@ -116,7 +214,7 @@ impl Tasks<DolphinBackend> {
     }
 }
 
-const HANS_ADDRESS: usize = 0x8029_7E4B;
+pub(crate) const HANS_ADDRESS: usize = 0x8029_7E4B;
 impl Hans<DolphinBackend> {
     fn new(base_addr: usize, handle: ProcessHandle) -> Self {
         Self {
@@ -125,7 +223,7 @@ impl Hans<DolphinBackend> {
     }
 }
 
-const POWERS_ADDRESS: usize = 0x803C_0F15;
+pub(crate) const POWERS_ADDRESS: usize = 0x803C_0F15;
 impl PowerUps<DolphinBackend> {
     fn new(base_addr: usize, handle: ProcessHandle) -> Self {
         Self {
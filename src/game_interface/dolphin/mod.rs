@@ -1,6 +1,6 @@
 //! Dolphin backend for [`GameInterface`](super::GameInterface)
-use log::{debug, error, trace};
-use process_memory::{CopyAddress, TryIntoProcessHandle};
+use log::{debug, error, trace, warn};
+use process_memory::TryIntoProcessHandle;
 use sysinfo::{PidExt, ProcessExt, System, SystemExt};
 use tap::TapFallible;
 use thiserror::Error;
@@ -9,9 +9,11 @@ use crate::game_interface::{GameInterface, InterfaceError};
 
 use self::dolphin_var::DolphinBackend;
 
-use super::{InterfaceProvider, InterfaceResult};
+use super::{
+    build::{self, Build, KnownBuild},
+    InterfaceProvider, InterfaceResult,
+};
 
-mod data_member;
 pub mod dolphin_var;
 
 const REGION_SIZE: usize = 0x200_0000;
@@ -39,9 +41,19 @@ impl From<std::io::Error> for Error {
 }
 
 /// Provider for [`GameInterface<DolphinBackend>`]
+///
+/// When hooking, the running build of the game is detected (see [`build`](super::build)). Unknown builds are refused
+/// with an [`InterfaceError::UnknownBuild`] unless allowed with [`allow_unknown_builds`](Self::allow_unknown_builds).
+/// Builds matched by their disc header alone are also refused, which includes the retail game with the default
+/// [`build::known_builds`].
+///
+/// When Dolphin becomes unhooked, its [`GameInterface`] is dropped, which reverts every patch applied through a
+/// [`PatchManager`](super::patch::PatchManager) for it.
 pub struct DolphinInterface {
     system: System,
     state: DolphinState,
+    builds: Vec<KnownBuild>,
+    allow_unknown_builds: bool,
 }
 
 // This is really no different from an Option, but allows us to more easily extend `Dolphin` with
@@ -49,7 +61,7 @@ pub struct DolphinInterface {
 #[allow(clippy::large_enum_variant)]
 enum DolphinState {
     Unhooked,
-    Hooked(GameInterface<DolphinBackend>),
+    Hooked(GameInterface<DolphinBackend>, Build),
 }
 
 impl Default for DolphinInterface {
@@ -57,6 +69,8 @@ impl Default for DolphinInterface {
         Self {
             system: System::default(),
             state: DolphinState::Unhooked,
+            builds: build::known_builds(),
+            allow_unknown_builds: false,
         }
     }
 }
@@ -82,30 +96,56 @@ impl InterfaceProvider for DolphinInterface {
     fn is_available(&mut self) -> bool {
         self.get_interface_or_hook().is_ok()
     }
+
+    fn build(&self) -> Option<&Build> {
+        match &self.state {
+            DolphinState::Unhooked => None,
+            DolphinState::Hooked(_, build) => Some(build),
+        }
+    }
 }
 
 impl DolphinInterface {
+    /// Use `builds` instead of [`build::known_builds`] to detect the running build of the game.
+    #[must_use]
+    pub fn with_builds(mut self, builds: Vec<KnownBuild>) -> Self {
+        self.builds = builds;
+        self
+    }
+
+    /// Set whether to hook builds of the game that aren't known, logging a warning instead of failing.
+    ///
+    /// This includes builds only matched by their disc header (see [`Build::is_header_only`]).
+    ///
+    /// **NOTE:** The crate's addresses are likely to be wrong for an unknown build, so reading or writing variables
+    /// may silently return incorrect values or corrupt the game's memory.
+    #[must_use]
+    pub fn allow_unknown_builds(mut self, allow: bool) -> Self {
+        self.allow_unknown_builds = allow;
+        self
+    }
+
     fn get_interface_or_hook(&mut self) -> InterfaceResult<&mut GameInterface<DolphinBackend>> {
         let interface = match self.state {
             DolphinState::Unhooked => {
-                let interface = self.hook()?;
-                self.state = DolphinState::Hooked(interface);
+                let (interface, build) = self.hook()?;
+                self.state = DolphinState::Hooked(interface, build);
                 match self.state {
                     DolphinState::Unhooked => unreachable!(),
-                    DolphinState::Hooked(ref mut interface) => interface,
+                    DolphinState::Hooked(ref mut interface, _) => interface,
                 }
             }
-            DolphinState::Hooked(ref mut interface) => interface,
+            DolphinState::Hooked(ref mut interface, _) => interface,
         };
         Ok(interface)
     }
 
     /// Attempt to hook Dolphin
     ///
-    /// Dolphin is considered "hooked" when it's process is found and the region of memory used
-    /// for emulating the GameCube's memory is located. This method will always attempt to hook
-    /// Dolphin when called, even if already hooked.
-    fn hook(&mut self) -> InterfaceResult<GameInterface<DolphinBackend>> {
+    /// Dolphin is considered "hooked" when it's process is found, the region of memory used
+    /// for emulating the GameCube's memory is located and a supported build of the game is running.
+    /// This method will always attempt to hook Dolphin when called, even if already hooked.
+    fn hook(&mut self) -> InterfaceResult<(GameInterface<DolphinBackend>, Build)> {
         self.system.refresh_processes();
 
         let procs = self.system.processes_by_name(PROCESS_NAME);
@@ -135,16 +175,23 @@ impl DolphinInterface {
 
         let handle = pid.try_into_process_handle()?;
 
-        // Make sure that the currently running game is BfBB
-        const GAME_CODE: &[u8; 6] = b"GQPE78";
-        let mut buf = [0u8; 6];
-        handle.copy_address(base_address, &mut buf)?;
-        if &buf != GAME_CODE {
-            error!("Incorrect game code: {buf:?}");
-            return Err(InterfaceError::IncorrectGame);
+        // Make sure that the currently running game is a build of BfBB that we support
+        let interface = GameInterface::<DolphinBackend>::new(base_address, handle);
+        let build = build::detect(&interface, &self.builds)?;
+        if !build.is_known() {
+            let reason = if build.is_header_only() {
+                "Unverified"
+            } else {
+                "Unknown"
+            };
+            if !self.allow_unknown_builds {
+                error!("{reason} build of the game: {build}");
+                return Err(InterfaceError::UnknownBuild(Box::new(build)));
+            }
+            warn!("{reason} build of the game: {build}. Addresses may be incorrect.");
         }
 
-        Ok(GameInterface::<DolphinBackend>::new(base_address, handle))
+        Ok((interface, build))
     }
 }
 
@@ -0,0 +1,324 @@
+//! Variables whose type is only known at runtime.
+//!
+//! Variables loaded from external files (such as Dolphin Memory Engine watch lists) describe their type as data, so
+//! they can't be represented by a [`GameVar`] of a fixed type. A [`DynVar`] reads and writes a [`DynValue`] of its
+//! [`DynType`] instead.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::dynamic::DynType;
+//! use bfbb::game_interface::game_var::{GameVar, InterfaceBackend};
+//! use bfbb::game_interface::{GameInterface, InterfaceResult};
+//!
+//! /// Print the value of a variable described by a user.
+//! fn print_var<F: InterfaceBackend>(
+//!     interface: &GameInterface<F>,
+//!     path: &[usize],
+//!     ty: DynType,
+//! ) -> InterfaceResult<()> {
+//!     println!("{}", interface.dyn_var(path, ty).get()?);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ```
+//! use bfbb::game_interface::dynamic::{DynType, DynValue};
+//! use bfbb::game_interface::game_var::{GameVar, GameVarMut};
+//! use bfbb::game_interface::mock::MockInterface;
+//! use bfbb::game_interface::{InterfaceError, InterfaceResult};
+//!
+//! # fn main() -> InterfaceResult<()> {
+//! let mut interface = MockInterface::default();
+//! interface.write_bytes(0x803C_205C, &9u32.to_be_bytes())?;
+//! let mut var = interface.dyn_var([0x803C_205C], DynType::U32);
+//! assert_eq!(var.get()?, DynValue::U32(9));
+//!
+//! var.set(DynValue::U32(75))?;
+//! let mut bytes = [0; 4];
+//! interface.read_bytes(0x803C_205C, &mut bytes)?;
+//! assert_eq!(bytes, 75u32.to_be_bytes());
+//! assert!(matches!(var.set(DynValue::U8(1)), Err(InterfaceError::TypeMismatch)));
+//!
+//! let mut name = interface.dyn_var([0x8000_1000], DynType::String(8));
+//! name.set(DynValue::String("HB01".into()))?;
+//! assert_eq!(name.get()?, DynValue::String("HB01".into()));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use bytemuck::CheckedBitPattern;
+
+use crate::endian::EndianAware;
+
+use super::{
+    game_var::{GameVar, GameVarMut, InterfaceBackend},
+    ptr,
+    string::{self, Encoding},
+    InterfaceError, InterfaceResult,
+};
+
+/// The type of a [`DynVar`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum DynType {
+    /// `u8`
+    U8,
+    /// `i8`
+    I8,
+    /// `u16`
+    U16,
+    /// `i16`
+    I16,
+    /// `u32`
+    U32,
+    /// `i32`
+    I32,
+    /// `f32`
+    F32,
+    /// `f64`
+    F64,
+    /// An ASCII string of at most this many bytes, ending at the first NUL byte.
+    String(usize),
+    /// This many raw bytes.
+    Bytes(usize),
+}
+
+/// Formats a type as its name, such as `u32` or `string[4]`.
+impl Display for DynType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::U8 => write!(f, "u8"),
+            Self::I8 => write!(f, "i8"),
+            Self::U16 => write!(f, "u16"),
+            Self::I16 => write!(f, "i16"),
+            Self::U32 => write!(f, "u32"),
+            Self::I32 => write!(f, "i32"),
+            Self::F32 => write!(f, "f32"),
+            Self::F64 => write!(f, "f64"),
+            Self::String(len) => write!(f, "string[{len}]"),
+            Self::Bytes(len) => write!(f, "bytes[{len}]"),
+        }
+    }
+}
+
+/// Parses a type from its name, as formatted by [`Display`].
+///
+/// ```
+/// use bfbb::game_interface::dynamic::DynType;
+///
+/// assert_eq!("u32".parse(), Ok(DynType::U32));
+/// assert_eq!("string[4]".parse(), Ok(DynType::String(4)));
+/// assert!("u128".parse::<DynType>().is_err());
+/// ```
+impl FromStr for DynType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sized = |prefix: &str| {
+            s.strip_prefix(prefix)?
+                .strip_prefix('[')?
+                .strip_suffix(']')?
+                .parse()
+                .ok()
+        };
+        Ok(match s {
+            "u8" => Self::U8,
+            "i8" => Self::I8,
+            "u16" => Self::U16,
+            "i16" => Self::I16,
+            "u32" => Self::U32,
+            "i32" => Self::I32,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            _ => {
+                if let Some(len) = sized("string") {
+                    Self::String(len)
+                } else if let Some(len) = sized("bytes") {
+                    Self::Bytes(len)
+                } else {
+                    return Err("Not a valid variable type");
+                }
+            }
+        })
+    }
+}
+
+/// A value read from or written to a [`DynVar`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum DynValue {
+    /// `u8`
+    U8(u8),
+    /// `i8`
+    I8(i8),
+    /// `u16`
+    U16(u16),
+    /// `i16`
+    I16(i16),
+    /// `u32`
+    U32(u32),
+    /// `i32`
+    I32(i32),
+    /// `f32`
+    F32(f32),
+    /// `f64`
+    F64(f64),
+    /// An ASCII string.
+    String(String),
+    /// Raw bytes.
+    Bytes(Vec<u8>),
+}
+
+impl DynValue {
+    /// Returns `true` if this value can be written to a [`DynVar`] of type `ty`.
+    #[must_use]
+    pub fn is_type(&self, ty: DynType) -> bool {
+        match (self, ty) {
+            (Self::U8(_), DynType::U8)
+            | (Self::I8(_), DynType::I8)
+            | (Self::U16(_), DynType::U16)
+            | (Self::I16(_), DynType::I16)
+            | (Self::U32(_), DynType::U32)
+            | (Self::I32(_), DynType::I32)
+            | (Self::F32(_), DynType::F32)
+            | (Self::F64(_), DynType::F64) => true,
+            (Self::String(s), DynType::String(len)) => s.is_ascii() && s.len() <= len,
+            (Self::Bytes(b), DynType::Bytes(len)) => b.len() == len,
+            _ => false,
+        }
+    }
+}
+
+impl Display for DynValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::U8(x) => write!(f, "{x}"),
+            Self::I8(x) => write!(f, "{x}"),
+            Self::U16(x) => write!(f, "{x}"),
+            Self::I16(x) => write!(f, "{x}"),
+            Self::U32(x) => write!(f, "{x}"),
+            Self::I32(x) => write!(f, "{x}"),
+            Self::F32(x) => write!(f, "{x}"),
+            Self::F64(x) => write!(f, "{x}"),
+            Self::String(s) => write!(f, "{s:?}"),
+            Self::Bytes(b) => {
+                for (i, byte) in b.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{byte:02X}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A [`GameVarMut`] whose type is only known at runtime.
+pub struct DynVar<F: InterfaceBackend> {
+    path: Vec<usize>,
+    ty: DynType,
+    handle: F::Handle,
+}
+
+impl<F: InterfaceBackend> DynVar<F> {
+    /// Create a new [`DynVar`] of type `ty` at the location given by the pointer-path `path`.
+    ///
+    /// See [`InterfaceBackend::create_var`] for how `path` is followed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `path` is empty.
+    pub fn new(handle: &F::Handle, path: impl Into<Vec<usize>>, ty: DynType) -> Self {
+        let path = path.into();
+        assert!(!path.is_empty(), "A variable's path must not be empty");
+        Self {
+            path,
+            ty,
+            handle: handle.clone(),
+        }
+    }
+
+    /// The pointer-path of this variable.
+    #[must_use]
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// The type of this variable.
+    #[must_use]
+    pub fn ty(&self) -> DynType {
+        self.ty
+    }
+
+    fn get_as<T: CheckedBitPattern + EndianAware>(&self) -> InterfaceResult<T> {
+        F::create_var(&self.handle, &self.path).get()
+    }
+
+    fn set_as<T: CheckedBitPattern + EndianAware>(&self, value: T) -> InterfaceResult<()> {
+        F::create_var(&self.handle, &self.path).set(value)
+    }
+
+    fn set_bytes(&self, bytes: &[u8]) -> InterfaceResult<()> {
+        let addr = ptr::resolve_path::<F>(&self.handle, &self.path)?;
+        F::write_bytes(&self.handle, addr, bytes)
+    }
+}
+
+impl<F: InterfaceBackend> GameVar for DynVar<F> {
+    type Target = DynValue;
+
+    fn get(&self) -> InterfaceResult<DynValue> {
+        Ok(match self.ty {
+            DynType::U8 => DynValue::U8(self.get_as()?),
+            DynType::I8 => DynValue::I8(self.get_as()?),
+            DynType::U16 => DynValue::U16(self.get_as()?),
+            DynType::I16 => DynValue::I16(self.get_as()?),
+            DynType::U32 => DynValue::U32(self.get_as()?),
+            DynType::I32 => DynValue::I32(self.get_as()?),
+            DynType::F32 => DynValue::F32(self.get_as()?),
+            DynType::F64 => DynValue::F64(self.get_as()?),
+            DynType::String(len) => DynValue::String(Encoding::Ascii.decode(
+                &string::read_bytes::<F>(&self.handle, &self.path, len, true)?,
+            )),
+            DynType::Bytes(len) => DynValue::Bytes(string::read_bytes::<F>(
+                &self.handle,
+                &self.path,
+                len,
+                false,
+            )?),
+        })
+    }
+}
+
+impl<F: InterfaceBackend> GameVarMut for DynVar<F> {
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError::TypeMismatch`] if `value` is not of this variable's type.
+    fn set(&mut self, value: DynValue) -> InterfaceResult<()> {
+        if !value.is_type(self.ty) {
+            return Err(InterfaceError::TypeMismatch);
+        }
+        match value {
+            DynValue::U8(x) => self.set_as(x),
+            DynValue::I8(x) => self.set_as(x),
+            DynValue::U16(x) => self.set_as(x),
+            DynValue::I16(x) => self.set_as(x),
+            DynValue::U32(x) => self.set_as(x),
+            DynValue::I32(x) => self.set_as(x),
+            DynValue::F32(x) => self.set_as(x),
+            DynValue::F64(x) => self.set_as(x),
+            DynValue::String(s) => {
+                // Pad with NUL to terminate the string if there is room
+                let mut bytes = s.into_bytes();
+                if let DynType::String(len) = self.ty {
+                    bytes.resize(len.min(bytes.len() + 1), 0);
+                }
+                self.set_bytes(&bytes)
+            }
+            DynValue::Bytes(b) => self.set_bytes(&b),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Access to every entity within the currently loaded scene.
+//!
+//! Every object in a scene (including triggers, timers, counters and other non-visual objects) is stored as an
+//! `xBase` in an array owned by the scene. This module allows iterating over that array and accessing any entity
+//! by its index within it.
+//!
+//! See also: [decomp](https://github.com/bfbbdecomp/bfbb/blob/master/include/xBase.h)
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::game_var::{GameVar, InterfaceBackend};
+//! use bfbb::game_interface::{GameInterface, InterfaceResult};
+//!
+//! fn print_entities<F: InterfaceBackend>(interface: &GameInterface<F>) -> InterfaceResult<()> {
+//!     for entity in interface.entities.iter()? {
+//!         let id = entity.asset_id.get()?;
+//!         println!("{}: {id:#010X} (type {})", entity.index, entity.base_type.get()?);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use bytemuck::CheckedBitPattern;
+
+use crate::{asset::str_hash, endian::EndianAware};
+
+use super::{
+    game_var::{GameVar, InterfaceBackend},
+    InterfaceResult,
+};
+
+const ID_OFFSET: usize = 0x0;
+const BASE_TYPE_OFFSET: usize = 0x4;
+const LINK_COUNT_OFFSET: usize = 0x5;
+const BASE_FLAGS_OFFSET: usize = 0x6;
+
+/// The array of entities of the current scene.
+///
+/// **NOTE:** Entities are only valid while the scene they were obtained from is loaded.
+pub struct Entities<F: InterfaceBackend> {
+    /// The number of entities in the current scene.
+    pub count: F::Var<u32>,
+    /// Pointer-path to the scene's array of entity pointers.
+    pub(crate) array: Vec<usize>,
+    pub(crate) handle: F::Handle,
+}
+
+/// [`GameVar`]s for the base fields shared by every entity.
+#[non_exhaustive]
+pub struct Entity<F: InterfaceBackend> {
+    /// Index of this entity within the scene's entity array.
+    pub index: usize,
+    /// The asset id that this entity was created from.
+    pub asset_id: F::Mut<u32>,
+    /// The `xBaseType` of this entity.
+    pub base_type: F::Mut<u8>,
+    /// The number of event links sent by this entity.
+    pub link_count: F::Mut<u8>,
+    /// A bitfield of this entity's base flags. The first bit determines if the entity is enabled or not.
+    pub flags: F::Mut<u16>,
+    path: Vec<usize>,
+    handle: F::Handle,
+}
+
+impl<F: InterfaceBackend> Entities<F> {
+    /// Get the entity at `index` within the scene's entity array.
+    ///
+    /// Returns `Ok(None)` if `index` is out of bounds for the current scene.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn get(&self, index: usize) -> InterfaceResult<Option<Entity<F>>> {
+        if index >= self.count.get()? as usize {
+            return Ok(None);
+        }
+        Ok(Some(self.entity(index)))
+    }
+
+    /// Iterate over every entity in the current scene.
+    ///
+    /// The number of entities is read once when this is called.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn iter(&self) -> InterfaceResult<impl Iterator<Item = Entity<F>> + '_> {
+        let count = self.count.get()? as usize;
+        Ok((0..count).map(|i| self.entity(i)))
+    }
+
+    /// Find the first entity in the current scene created from the asset with id `asset_id`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn find(&self, asset_id: u32) -> InterfaceResult<Option<Entity<F>>> {
+        for entity in self.iter()? {
+            if entity.asset_id.get()? == asset_id {
+                return Ok(Some(entity));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the first entity in the current scene created from the asset named `name`.
+    ///
+    /// Unlike indexes into the entity array, asset names are stable between builds of the game.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::game_var::{GameVarMut, InterfaceBackend};
+    /// use bfbb::game_interface::{GameInterface, InterfaceResult};
+    ///
+    /// fn disable_entity<F: InterfaceBackend>(
+    ///     interface: &mut GameInterface<F>,
+    ///     name: &str,
+    /// ) -> InterfaceResult<bool> {
+    ///     match interface.entities.find_by_name(name)? {
+    ///         Some(mut entity) => {
+    ///             entity.flags.set(0)?;
+    ///             Ok(true)
+    ///         }
+    ///         None => Ok(false),
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn find_by_name(&self, name: &str) -> InterfaceResult<Option<Entity<F>>> {
+        self.find(str_hash(name))
+    }
+
+    fn entity(&self, index: usize) -> Entity<F> {
+        let mut path = self.array.clone();
+        path.push(index * std::mem::size_of::<u32>());
+        path.push(0);
+        let field = |offset: usize| {
+            let mut path = path.clone();
+            *path.last_mut().unwrap() = offset;
+            path
+        };
+
+        Entity {
+            index,
+            asset_id: F::create_var(&self.handle, &field(ID_OFFSET)),
+            base_type: F::create_var(&self.handle, &field(BASE_TYPE_OFFSET)),
+            link_count: F::create_var(&self.handle, &field(LINK_COUNT_OFFSET)),
+            flags: F::create_var(&self.handle, &field(BASE_FLAGS_OFFSET)),
+            handle: self.handle.clone(),
+            path,
+        }
+    }
+}
+
+impl<F: InterfaceBackend> Entity<F> {
+    /// Create a [`GameVar`] for a field of this entity, `offset` bytes from the start of its `xBase`.
+    ///
+    /// This allows accessing fields of derived types (e.g. `xEnt`) that are not provided by this struct.
+    #[must_use]
+    pub fn field<T: CheckedBitPattern + EndianAware>(&self, offset: usize) -> F::Mut<T> {
+        let mut path = self.path.clone();
+        *path.last_mut().unwrap() = offset;
+        F::create_var(&self.handle, &path)
+    }
+}
@@ -0,0 +1,161 @@
+//! Named views over bitfield variables.
+//!
+//! Many of the game's variables are bitfields where each bit has a separate meaning. [`FlagVar`] wraps one of
+//! these variables so its bits can be read and modified by name rather than by bare literal.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::flags::{EntityFlags, FlagVar};
+//! use bfbb::game_interface::game_var::GameVar;
+//! use bfbb::game_interface::mock::mock_vars::MockVar;
+//! use bfbb::game_interface::InterfaceResult;
+//!
+//! # fn main() -> InterfaceResult<()> {
+//! let mut raw = MockVar::new(0x81u8);
+//! let mut flags = FlagVar::<_, EntityFlags>::new(&mut raw);
+//! assert!(flags.contains(EntityFlags::ENABLED)?);
+//!
+//! flags.remove(EntityFlags::ENABLED)?;
+//! assert!(!flags.get()?.contains(EntityFlags::ENABLED));
+//!
+//! // Unnamed bits are left untouched
+//! assert_eq!(raw.value, 0x80);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::marker::PhantomData;
+
+use bitflags::{bitflags, Flags};
+
+use super::{
+    game_var::{GameVar, GameVarMut},
+    InterfaceResult,
+};
+
+bitflags! {
+    /// Flags of an entity's `xEnt::flags` field.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct EntityFlags: u8 {
+        /// The entity is enabled. Disabled entities are not drawn and can not be interacted with.
+        const ENABLED = 0x01;
+    }
+}
+
+bitflags! {
+    /// Flags of a spatula pickup's model state.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ModelState: u32 {
+        /// Set while the pickup can be collected. The exact meaning of this bit is unverified.
+        const AVAILABLE = 0x02;
+        /// The pickup's collection animation is playing.
+        const COLLECTING = 0x04;
+        /// The pickup has been collected.
+        const COLLECTED = 0x08;
+    }
+}
+
+bitflags! {
+    /// Flags controlling Hans, the hand that allows the player to skip to a task from the pause menu.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct HansFlags: u8 {
+        /// Hans is disabled.
+        const DISABLED = 0x04;
+    }
+}
+
+/// A [`GameVar`] that views another variable's bits as the flags type `B`.
+///
+/// Bits without a name in `B` are preserved when modifying flags.
+#[derive(Debug, Clone)]
+pub struct FlagVar<V, B> {
+    var: V,
+    _flags: PhantomData<B>,
+}
+
+impl<V, B> FlagVar<V, B> {
+    /// View the bits of `var` as `B`.
+    pub fn new(var: V) -> Self {
+        Self {
+            var,
+            _flags: PhantomData,
+        }
+    }
+}
+
+impl<V: GameVar, B: Flags<Bits = V::Target>> GameVar for FlagVar<V, B> {
+    type Target = B;
+
+    fn get(&self) -> InterfaceResult<B> {
+        self.var.get().map(B::from_bits_retain)
+    }
+}
+
+impl<V: GameVarMut, B: Flags<Bits = V::Target>> GameVarMut for FlagVar<V, B> {
+    fn set(&mut self, value: B) -> InterfaceResult<()> {
+        self.var.set(value.bits())
+    }
+}
+
+impl<V: GameVar, B: Flags<Bits = V::Target>> FlagVar<V, B> {
+    /// Whether all of `flags` are currently set.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn contains(&self, flags: B) -> InterfaceResult<bool> {
+        Ok(self.get()?.contains(flags))
+    }
+}
+
+impl<V: GameVarMut, B: Flags<Bits = V::Target>> FlagVar<V, B> {
+    /// Read the current flags, modify them with `f` and write them back. Returns the newly written flags.
+    ///
+    /// **NOTE:** This is not truly atomic, the game may modify the variable between the read and the write.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn update(&mut self, f: impl FnOnce(&mut B)) -> InterfaceResult<B> {
+        let mut flags = self.get()?;
+        f(&mut flags);
+        self.set(B::from_bits_retain(flags.bits()))?;
+        Ok(flags)
+    }
+
+    /// Set all of `flags`, leaving other bits unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn insert(&mut self, flags: B) -> InterfaceResult<()> {
+        self.update(|f| f.insert(flags)).map(|_| ())
+    }
+
+    /// Clear all of `flags`, leaving other bits unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn remove(&mut self, flags: B) -> InterfaceResult<()> {
+        self.update(|f| f.remove(flags)).map(|_| ())
+    }
+
+    /// Set or clear all of `flags` depending on `value`, leaving other bits unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn set_flags(&mut self, flags: B, value: bool) -> InterfaceResult<()> {
+        self.update(|f| f.set(flags, value)).map(|_| ())
+    }
+
+    /// Toggle all of `flags`, leaving other bits unchanged. Returns the newly written flags.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn toggle(&mut self, flags: B) -> InterfaceResult<B> {
+        self.update(|f| f.toggle(flags))
+    }
+}
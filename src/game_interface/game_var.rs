@@ -28,6 +28,45 @@ pub trait GameVar {
     ///
     /// May return an [`InterfaceError`](super::InterfaceError) if the value can not be read.
     fn get(&self) -> InterfaceResult<Self::Target>;
+
+    /// Create a [`GameVar`] whose value is this variable's value converted by `f`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::game_var::{GameVar, InterfaceBackend};
+    /// use bfbb::game_interface::{GameInterface, InterfaceResult};
+    ///
+    /// fn has_all_spatulas<F: InterfaceBackend>(interface: &GameInterface<F>) -> InterfaceResult<bool> {
+    ///     (&interface.spatula_count).map(|count| count >= 100).get()
+    /// }
+    /// ```
+    fn map<U, M>(self, f: M) -> Map<Self, M>
+    where
+        Self: Sized,
+        M: Fn(Self::Target) -> U,
+    {
+        Map { var: self, f }
+    }
+
+    /// Create a [`GameVar`] whose value is this variable's value converted by the fallible function `f`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::game_var::{GameVar, InterfaceBackend};
+    /// use bfbb::game_interface::{GameInterface, InterfaceResult};
+    /// use bfbb::Level;
+    ///
+    /// fn level<F: InterfaceBackend>(interface: &GameInterface<F>) -> impl GameVar<Target = Level> + '_ {
+    ///     (&interface.scene_id).try_map(|id| Ok(Level::try_from(id)?))
+    /// }
+    /// ```
+    fn try_map<U, M>(self, f: M) -> TryMap<Self, M>
+    where
+        Self: Sized,
+        M: Fn(Self::Target) -> InterfaceResult<U>,
+    {
+        TryMap { var: self, f }
+    }
 }
 
 /// Allows mutating a variable within BfBB
@@ -38,6 +77,119 @@ pub trait GameVarMut: GameVar {
     ///
     /// May return an [`InterfaceError`](super::InterfaceError) if the value can not be read.
     fn set(&mut self, value: Self::Target) -> InterfaceResult<()>;
+
+    /// Create a [`GameVarMut`] of another type, converting values with `get` when reading and `set` when writing.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::game_var::{GameVar, GameVarMut};
+    /// use bfbb::game_interface::mock::mock_vars::MockVar;
+    /// use bfbb::game_interface::{InterfaceError, InterfaceResult};
+    /// use bfbb::TaskStatus;
+    ///
+    /// # fn main() -> InterfaceResult<()> {
+    /// let mut raw = MockVar::new(0i16);
+    /// let mut status = (&mut raw).bidirectional(
+    ///     |x| TaskStatus::try_from(x).map_err(|_| InterfaceError::DataUnavailable),
+    ///     i16::from,
+    /// );
+    /// status.set(TaskStatus::Complete)?;
+    /// assert_eq!(status.get()?, TaskStatus::Complete);
+    /// assert_eq!(raw.value, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn bidirectional<U, G, S>(self, get: G, set: S) -> Bidirectional<Self, G, S>
+    where
+        Self: Sized,
+        G: Fn(Self::Target) -> InterfaceResult<U>,
+        S: Fn(U) -> Self::Target,
+    {
+        Bidirectional {
+            var: self,
+            get,
+            set,
+        }
+    }
+}
+
+impl<V: GameVar + ?Sized> GameVar for &V {
+    type Target = V::Target;
+    fn get(&self) -> InterfaceResult<Self::Target> {
+        (**self).get()
+    }
+}
+
+impl<V: GameVar + ?Sized> GameVar for &mut V {
+    type Target = V::Target;
+    fn get(&self) -> InterfaceResult<Self::Target> {
+        (**self).get()
+    }
+}
+
+impl<V: GameVarMut + ?Sized> GameVarMut for &mut V {
+    fn set(&mut self, value: Self::Target) -> InterfaceResult<()> {
+        (**self).set(value)
+    }
+}
+
+/// A [`GameVar`] that converts the value of another. Created by [`GameVar::map`].
+#[derive(Debug, Clone)]
+pub struct Map<V, M> {
+    var: V,
+    f: M,
+}
+
+impl<U, V: GameVar, M: Fn(V::Target) -> U> GameVar for Map<V, M> {
+    type Target = U;
+    fn get(&self) -> InterfaceResult<U> {
+        self.var.get().map(&self.f)
+    }
+}
+
+/// A [`GameVar`] that fallibly converts the value of another. Created by [`GameVar::try_map`].
+#[derive(Debug, Clone)]
+pub struct TryMap<V, M> {
+    var: V,
+    f: M,
+}
+
+impl<U, V: GameVar, M: Fn(V::Target) -> InterfaceResult<U>> GameVar for TryMap<V, M> {
+    type Target = U;
+    fn get(&self) -> InterfaceResult<U> {
+        self.var.get().and_then(&self.f)
+    }
+}
+
+/// A [`GameVarMut`] that converts the value of another in both directions. Created by
+/// [`GameVarMut::bidirectional`].
+#[derive(Debug, Clone)]
+pub struct Bidirectional<V, G, S> {
+    var: V,
+    get: G,
+    set: S,
+}
+
+impl<U, V, G, S> GameVar for Bidirectional<V, G, S>
+where
+    V: GameVar,
+    G: Fn(V::Target) -> InterfaceResult<U>,
+{
+    type Target = U;
+    fn get(&self) -> InterfaceResult<U> {
+        self.var.get().and_then(&self.get)
+    }
+}
+
+impl<U, V, G, S> GameVarMut for Bidirectional<V, G, S>
+where
+    V: GameVarMut,
+    G: Fn(V::Target) -> InterfaceResult<U>,
+    S: Fn(U) -> V::Target,
+{
+    fn set(&mut self, value: U) -> InterfaceResult<()> {
+        self.var.set((self.set)(value))
+    }
 }
 
 /// A trait with type constructors for [`GameVar`]s that are generic over an implementation (backend).
@@ -61,4 +213,31 @@ pub trait InterfaceBackend {
     type Var<T: CheckedBitPattern + EndianAware>: GameVar<Target = T>;
     /// Type constructor for a mutable [`GameVar`]
     type Mut<T: CheckedBitPattern + EndianAware>: GameVarMut<Target = T>;
+    /// Backend-specific state required to create new [`GameVar`]s at runtime, such as a handle to an emulator process.
+    type Handle: Clone;
+
+    /// Create a new [`GameVarMut`] given a pointer-path within the game's memory-space.
+    ///
+    /// Every offset except the last is added to the previous value and dereferenced as a GameCube pointer,
+    /// the final offset is added to the last pointer to get the location of the variable.
+    fn create_var<T: CheckedBitPattern + EndianAware>(
+        handle: &Self::Handle,
+        path: &[usize],
+    ) -> Self::Mut<T>;
+
+    /// Read `buf.len()` bytes of the game's memory, starting at the GameCube virtual address `addr`.
+    ///
+    /// Bytes are read as-is, in the game's (big-endian) byte order.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the memory can not be read.
+    fn read_bytes(handle: &Self::Handle, addr: u32, buf: &mut [u8]) -> InterfaceResult<()>;
+
+    /// Write `bytes` to the game's memory, starting at the GameCube virtual address `addr`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the memory can not be written.
+    fn write_bytes(handle: &Self::Handle, addr: u32, bytes: &[u8]) -> InterfaceResult<()>;
 }
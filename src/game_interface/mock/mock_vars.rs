@@ -2,17 +2,30 @@
 //!
 //! This may be useful for testing logic against some known state.
 
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
 use bytemuck::CheckedBitPattern;
 use strum::IntoEnumIterator;
 
 use crate::{
     endian::EndianAware,
     game_interface::{
+        dolphin::dolphin_var::{
+            task_entity_path, task_menu_count_path, GAME_MODE_ADDRESS, GAME_OSTRICH_ADDRESS,
+            GAME_STATE_ADDRESS, HANS_ADDRESS, LAB_DOOR_ADDRESS, LOADING_ADDRESS, POWERS_ADDRESS,
+            SCENE_PTR_ADDRESS, SHINY_COUNT_ADDRESS, SOCK_COUNT_ADDRESS, SPATULA_COUNT_ADDRESS,
+            TASK_FLAGS_OFFSET, TASK_STATE_OFFSET,
+        },
+        entity::Entities,
         game_var::{GameVar, GameVarMut, InterfaceBackend},
-        GameInterface, Hans, PowerUps, Task, Tasks,
+        patch::PatchRegistry,
+        ptr, GameInterface, Hans, InterfaceResult, PowerUps, Task, Tasks,
     },
     game_state::{GameMode, GameOstrich, GameState},
-    Level, Spatula,
+    Level, SceneId, Spatula,
 };
 
 /// A mock implementation for [`InterfaceBackend`]
@@ -20,36 +33,203 @@ pub enum MockBackend {}
 impl InterfaceBackend for MockBackend {
     type Var<T: CheckedBitPattern + EndianAware> = MockVar<T>;
     type Mut<T: CheckedBitPattern + EndianAware> = MockVar<T>;
+    type Handle = MockMemory;
+
+    /// Variables created at runtime are stored in [`MockMemory`], following `path` through it in the same way as the
+    /// game's memory.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::game_var::{GameVar, GameVarMut};
+    /// use bfbb::game_interface::mock::MockInterface;
+    /// use bfbb::game_interface::InterfaceResult;
+    ///
+    /// # fn main() -> InterfaceResult<()> {
+    /// let mut interface = MockInterface::default();
+    /// interface.write_bytes(0x8000_1000, &20u32.to_be_bytes())?;
+    /// assert_eq!(interface.create_var::<u32>(&[0x8000_1000]).get()?, 20);
+    ///
+    /// // Follow a pointer at 0x80002000 to the value 0x10 bytes past 0x80001000
+    /// interface.write_bytes(0x8000_2000, &0x8000_1000u32.to_be_bytes())?;
+    /// let mut var = interface.create_var::<u32>(&[0x8000_2000, 0x10]);
+    /// var.set(75)?;
+    /// let mut bytes = [0; 4];
+    /// interface.read_bytes(0x8000_1010, &mut bytes)?;
+    /// assert_eq!(u32::from_be_bytes(bytes), 75);
+    ///
+    /// // A null pointer can't be followed
+    /// assert!(interface.create_var::<u32>(&[0x8000_3000, 0x10]).get().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn create_var<T: CheckedBitPattern + EndianAware>(
+        handle: &Self::Handle,
+        path: &[usize],
+    ) -> Self::Mut<T> {
+        let zeroed = vec![0; std::mem::size_of::<T>()];
+        MockVar {
+            value: bytemuck::checked::try_pod_read_unaligned(&zeroed)
+                .expect("Game variables should be valid when zeroed"),
+            link: Some(MockLink {
+                memory: handle.clone(),
+                path: path.to_vec(),
+            }),
+        }
+    }
+
+    fn read_bytes(handle: &Self::Handle, addr: u32, buf: &mut [u8]) -> InterfaceResult<()> {
+        ptr::check_range(addr, buf.len())?;
+        let memory = handle
+            .bytes
+            .lock()
+            .expect("Mock memory lock should not be poisoned");
+        for (i, b) in (0..).zip(buf.iter_mut()) {
+            *b = memory
+                .get(&addr.wrapping_add(i))
+                .copied()
+                .unwrap_or_default();
+        }
+        Ok(())
+    }
+
+    fn write_bytes(handle: &Self::Handle, addr: u32, bytes: &[u8]) -> InterfaceResult<()> {
+        ptr::check_range(addr, bytes.len())?;
+        let mut memory = handle
+            .bytes
+            .lock()
+            .expect("Mock memory lock should not be poisoned");
+        memory.extend(
+            (0..)
+                .map(|i| addr.wrapping_add(i))
+                .zip(bytes.iter().copied()),
+        );
+        Ok(())
+    }
 }
 
+/// Mock implementation for [`InterfaceBackend::Handle`]
+///
+/// A sparse map of the game's memory for [`InterfaceBackend::read_bytes`] and [`InterfaceBackend::write_bytes`].
+/// Bytes that have never been written read as zero. Clones share the same memory.
+#[derive(Clone, Default)]
+pub struct MockMemory {
+    bytes: Arc<Mutex<BTreeMap<u32, u8>>>,
+}
+
+// The game's heap is not mocked, so the structures that the game allocates there are placed at these addresses instead.
+const MOCK_SCENE_ADDRESS: u32 = 0x8100_0000;
+const MOCK_ENTITY_ARRAY_ADDRESS: u32 = 0x8100_1000;
+const MOCK_ENTITIES_ADDRESS: u32 = 0x8101_0000;
+const MOCK_ENTITY_SIZE: u32 = 0x200;
+const MOCK_COUNTERS_ADDRESS: u32 = 0x8108_0000;
+const MOCK_COUNTER_SIZE: u32 = 0x20;
+
+impl MockMemory {
+    fn write_ptr(&self, addr: usize, target: u32) {
+        MockBackend::write_bytes(self, addr as u32, &target.to_be_bytes())
+            .expect("Mock structures should be within the game's memory");
+    }
+}
+
+/// The built-in variables are stored in [`MockMemory`] at the same addresses as in the game, so they can also be
+/// accessed with [`read_bytes`](GameInterface::read_bytes) and [`write_bytes`](GameInterface::write_bytes).
+///
+/// # Examples
+/// ```
+/// use bfbb::game_interface::game_var::{GameVar, GameVarMut};
+/// use bfbb::game_interface::mock::MockInterface;
+/// use bfbb::game_interface::InterfaceResult;
+/// use bfbb::{Level, Spatula};
+///
+/// # fn main() -> InterfaceResult<()> {
+/// let mut interface = MockInterface::default();
+/// assert_eq!(interface.get_current_level()?, Level::SpongebobHouse);
+///
+/// interface.write_bytes(0x803C_205C, &50u32.to_be_bytes())?;
+/// assert_eq!(interface.spatula_count.get()?, 50);
+///
+/// interface.tasks[Spatula::OnTopOfThePineapple].menu_count.set(2)?;
+/// let ptr = interface.create_var::<u32>(&[0x802F_63C8 + 0xC + 0x14]).get()?;
+/// let mut count = [0; 2];
+/// interface.read_bytes(ptr + 0x14, &mut count)?;
+/// assert_eq!(i16::from_be_bytes(count), 2);
+/// # Ok(())
+/// # }
+/// ```
 impl Default for GameInterface<MockBackend> {
     fn default() -> Self {
-        Self {
-            is_loading: MockVar::default(),
-            game_state: MockVar::new(GameState::Play),
-            game_mode: MockVar::new(GameMode::Game),
-            game_ostrich: MockVar::new(GameOstrich::InScene),
-            hans: Hans::new(),
-            powers: PowerUps::new(),
-            scene_id: MockVar::new(Level::SpongebobHouse.into()),
-            spatula_count: MockVar::default(),
-            tasks: Tasks::new(),
-            lab_door_cost: MockVar::new(74),
+        let memory = MockMemory::default();
+
+        let slots = Spatula::iter()
+            .filter_map(|s| s.get_offset())
+            .max()
+            .map_or(0, |max| max + 1);
+        memory.write_ptr(SCENE_PTR_ADDRESS, MOCK_SCENE_ADDRESS);
+        memory.write_ptr(
+            MOCK_SCENE_ADDRESS as usize + 0x78,
+            MOCK_ENTITY_ARRAY_ADDRESS,
+        );
+        for i in 0..slots as u32 {
+            memory.write_ptr(
+                (MOCK_ENTITY_ARRAY_ADDRESS + i * 4) as usize,
+                MOCK_ENTITIES_ADDRESS + i * MOCK_ENTITY_SIZE,
+            );
         }
+
+        let mut interface = Self {
+            is_loading: MockBackend::create_var(&memory, &[LOADING_ADDRESS]),
+            game_state: MockBackend::create_var(&memory, &[GAME_STATE_ADDRESS]),
+            game_mode: MockBackend::create_var(&memory, &[GAME_MODE_ADDRESS]),
+            game_ostrich: MockBackend::create_var(&memory, &[GAME_OSTRICH_ADDRESS]),
+            hans: Hans::new(&memory),
+            powers: PowerUps::new(&memory),
+            scene_id: MockBackend::create_var(&memory, &[SCENE_PTR_ADDRESS, 0]),
+            spatula_count: MockBackend::create_var(&memory, &[SPATULA_COUNT_ADDRESS]),
+            shiny_count: MockBackend::create_var(&memory, &[SHINY_COUNT_ADDRESS]),
+            sock_count: MockBackend::create_var(&memory, &[SOCK_COUNT_ADDRESS]),
+            tasks: Tasks::new(&memory),
+            entities: Entities {
+                count: MockBackend::create_var(&memory, &[SCENE_PTR_ADDRESS, 0x74]),
+                array: vec![SCENE_PTR_ADDRESS, 0x78],
+                handle: memory.clone(),
+            },
+            lab_door_cost: MockBackend::create_var(&memory, &[LAB_DOOR_ADDRESS]),
+            handle: memory.clone(),
+            patches: PatchRegistry::default(),
+        };
+
+        let scene_id: SceneId = Level::SpongebobHouse.into();
+        MockBackend::write_bytes(&memory, MOCK_SCENE_ADDRESS, &scene_id.0)
+            .and_then(|()| {
+                MockBackend::create_var::<u32>(&memory, &[SCENE_PTR_ADDRESS, 0x74])
+                    .set(slots as u32)
+            })
+            .and_then(|()| interface.game_state.set(GameState::Play))
+            .and_then(|()| interface.game_mode.set(GameMode::Game))
+            .and_then(|()| interface.game_ostrich.set(GameOstrich::InScene))
+            .and_then(|()| interface.hans.flags.set(3))
+            .and_then(|()| interface.lab_door_cost.set(74))
+            .expect("Mock variables should be within the game's memory");
+        interface
     }
 }
 
 impl Tasks<MockBackend> {
-    fn new() -> Self {
+    fn new(memory: &MockMemory) -> Self {
         Self {
             arr: Spatula::iter()
-                .map(|s| {
+                .zip(0..)
+                .map(|(s, i)| {
+                    let menu_path = task_menu_count_path(s);
+                    memory.write_ptr(menu_path[0], MOCK_COUNTERS_ADDRESS + i * MOCK_COUNTER_SIZE);
                     (
                         s,
                         Task {
-                            menu_count: MockVar::default(),
-                            flags: Some(MockVar::default()),
-                            state: Some(MockVar::default()),
+                            menu_count: MockBackend::create_var(memory, &menu_path),
+                            flags: task_entity_path(s, TASK_FLAGS_OFFSET)
+                                .map(|path| MockBackend::create_var(memory, &path)),
+                            state: task_entity_path(s, TASK_STATE_OFFSET)
+                                .map(|path| MockBackend::create_var(memory, &path)),
                         },
                     )
                 })
@@ -59,35 +239,48 @@ impl Tasks<MockBackend> {
 }
 
 impl Hans<MockBackend> {
-    fn new() -> Self {
+    fn new(memory: &MockMemory) -> Self {
         Self {
-            flags: MockVar::new(3),
+            flags: MockBackend::create_var(memory, &[HANS_ADDRESS]),
         }
     }
 }
 
 impl PowerUps<MockBackend> {
-    fn new() -> Self {
+    fn new(memory: &MockMemory) -> Self {
         Self {
-            bubble_bowl: MockVar::default(),
-            cruise_bubble: MockVar::default(),
-            initial_bubble_bowl: MockVar::default(),
-            initial_cruise_bubble: MockVar::default(),
+            bubble_bowl: MockBackend::create_var(memory, &[POWERS_ADDRESS]),
+            cruise_bubble: MockBackend::create_var(memory, &[POWERS_ADDRESS + 1]),
+            initial_bubble_bowl: MockBackend::create_var(memory, &[POWERS_ADDRESS + 2]),
+            initial_cruise_bubble: MockBackend::create_var(memory, &[POWERS_ADDRESS + 3]),
         }
     }
 }
 
 /// A mock implementation for [`GameVar`] and [`GameVarMut`]
+///
+/// Variables created with [`InterfaceBackend::create_var`], including every built-in variable of a default
+/// [`MockInterface`](super::MockInterface), are stored in [`MockMemory`] instead and ignore `value`. Set up a scenario
+/// for those with [`GameVarMut::set`] (which is implemented for every [`MockVar`]) or
+/// [`write_bytes`](GameInterface::write_bytes).
 #[derive(Default)]
 pub struct MockVar<T> {
-    /// The mocked value. Accessing the value here directly allows setting up a scenario for a test,
-    /// including the ability to mutate [`GameVar`]s that normally are immuatble.
+    /// The mocked value of a variable created with [`MockVar::new`]. Accessing the value here directly allows
+    /// setting up a scenario for a test, including the ability to mutate [`GameVar`]s that normally are immuatble.
     pub value: T,
+    link: Option<MockLink>,
+}
+
+/// The location within [`MockMemory`] of a [`MockVar`] created at runtime.
+struct MockLink {
+    memory: MockMemory,
+    path: Vec<usize>,
 }
 
 impl<T> MockVar<T> {
-    fn new(value: T) -> Self {
-        Self { value }
+    /// Create a new variable holding `value`.
+    pub fn new(value: T) -> Self {
+        Self { value, link: None }
     }
 }
 
@@ -95,13 +288,19 @@ impl<T: CheckedBitPattern + EndianAware> GameVar for MockVar<T> {
     type Target = T;
 
     fn get(&self) -> crate::game_interface::InterfaceResult<Self::Target> {
-        Ok(self.value)
+        let Some(link) = &self.link else {
+            return Ok(self.value);
+        };
+        ptr::read_value::<MockBackend, T>(&link.memory, &link.path)
     }
 }
 
 impl<T: CheckedBitPattern + EndianAware> GameVarMut for MockVar<T> {
     fn set(&mut self, value: Self::Target) -> crate::game_interface::InterfaceResult<()> {
-        self.value = value;
-        Ok(())
+        let Some(link) = &self.link else {
+            self.value = value;
+            return Ok(());
+        };
+        ptr::write_value::<MockBackend, T>(&link.memory, &link.path, &value)
     }
 }
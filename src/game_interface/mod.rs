@@ -3,24 +3,51 @@
 use std::{
     collections::HashMap,
     ops::{Index, IndexMut},
+    time::Duration,
 };
 
+use bytemuck::CheckedBitPattern;
+use strum::IntoEnumIterator;
+
 use thiserror::Error;
 
 use crate::{
+    endian::EndianAware,
     game_state::{GameMode, GameOstrich, GameState},
-    Level, Spatula,
+    save::{Powers, SaveGame},
+    Level, NotALevelError, SceneId, Spatula, TaskStatus,
 };
 
-use self::game_var::{GameVar, GameVarMut, InterfaceBackend};
+use self::{
+    build::Build,
+    dynamic::{DynType, DynVar},
+    entity::Entities,
+    flags::{EntityFlags, FlagVar, HansFlags, ModelState},
+    game_var::{GameVar, GameVarMut, InterfaceBackend},
+    patch::PatchRegistry,
+    ptr::GamePtr,
+    string::{Encoding, StringVar},
+};
 
+pub mod build;
+#[cfg(feature = "dmw")]
+pub mod dmw;
 pub mod dolphin;
+pub mod dynamic;
+pub mod entity;
+pub mod flags;
 pub mod game_var;
 pub mod mock;
+pub mod patch;
+pub mod ptr;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod scan;
+pub mod string;
 
 /// Interact with BfBB in an abstract way.
 ///
-//// This struct allows accessing variables existing with a running instance of *Battle for Bikini Bottom*
+/// This struct allows accessing variables existing with a running instance of *Battle for Bikini Bottom*
 /// and performing actions on that instance in a way that is generic over a backend (e.g Dolphin and Xemu)
 ///
 /// NOTE: To a see a list of supported backends see the `Implementors` list of [`InterfaceBackend`]
@@ -44,6 +71,8 @@ pub struct GameInterface<F: InterfaceBackend> {
     pub powers: PowerUps<F>,
     /// Location of the ID for the current scene. Can be converted to a [`Level`](crate::Level) via [`TryFrom`].
     ///
+    /// See also [`get_current_scene`](Self::get_current_scene) and [`get_current_level`](Self::get_current_level).
+    ///
     /// # Examples
     /// ```
     /// use std::error::Error;
@@ -58,14 +87,29 @@ pub struct GameInterface<F: InterfaceBackend> {
     ///     Ok(interface.scene_id.get()?.try_into()?)
     /// }
     /// ```
-    pub scene_id: F::Var<[u8; 4]>,
+    pub scene_id: F::Var<SceneId>,
     /// Location of the spatula counter
     pub spatula_count: F::Mut<u32>,
+    /// Location of the shiny object counter
+    pub shiny_count: F::Mut<u32>,
+    /// Location of the total number of Patrick's socks collected
+    pub sock_count: F::Mut<u32>,
     /// [`Tasks`]
     pub tasks: Tasks<F>,
+    /// [`Entities`]
+    pub entities: Entities<F>,
 
     // TODO: This value is on the heap, it shouldn't be global like this
     lab_door_cost: F::Mut<u32>,
+    handle: F::Handle,
+    patches: PatchRegistry,
+}
+
+/// Reverts every patch applied through a [`PatchManager`](patch::PatchManager) for this interface.
+impl<F: InterfaceBackend> Drop for GameInterface<F> {
+    fn drop(&mut self) {
+        self.patches.revert_all::<F>(&self.handle);
+    }
 }
 
 /// A collection of [`Task`]s. Can be indexed by [`Spatula`]
@@ -95,13 +139,17 @@ pub struct Task<F: InterfaceBackend> {
     /// - `1` => Task is "incomplete", will be a silver spatula in the menu.
     /// - `2` => Task is "complete", will be a golden spatula in the menu.
     /// - `3` => Task is also silver in the menu, this appears to only be used by [`Spatula::InfestationAtTheKrustyKrab`],
-    ///          which uses this value for after clearing the robots, but before you've collected the spatula.
+    ///   which uses this value for after clearing the robots, but before you've collected the spatula.
     /// - `_` => No icon will appear for this task in the menu, just an empty bubble. You can not warp to it and
-    ///          attempting to will put the menu into an invalid state until a different unlocked task is selected.
+    ///   attempting to will put the menu into an invalid state until a different unlocked task is selected.
     pub menu_count: F::Mut<i16>,
     /// A bitfield of flags for a spatula entity. The first bit determines if the entity is enabled or not.
+    ///
+    /// See [`EntityFlags`] for named bits.
     pub flags: Option<F::Mut<u8>>,
     /// Another bitfield for a spatula entity.
+    ///
+    /// See [`ModelState`] for named bits.
     pub state: Option<F::Mut<u32>>,
 }
 /// [`GameVar`]s related to the bubble bowl and cruise-missile
@@ -133,18 +181,141 @@ impl<F: InterfaceBackend> GameInterface<F> {
         self.game_mode.set(GameMode::Game)
     }
 
+    /// Create a [`GameVarMut`] given a pointer-path within the game's memory-space.
+    ///
+    /// This is a backend-agnostic "escape-hatch" for accessing variables not implemented in the library. See
+    /// [`InterfaceBackend::create_var`] for how `path` is followed.
+    pub fn create_var<T: CheckedBitPattern + EndianAware>(&self, path: &[usize]) -> F::Mut<T> {
+        F::create_var(&self.handle, path)
+    }
+
+    /// Read `buf.len()` bytes of the game's memory, starting at the GameCube virtual address `addr`.
+    ///
+    /// Bytes are read as-is, in the game's (big-endian) byte order.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::mock::MockInterface;
+    /// use bfbb::game_interface::InterfaceResult;
+    ///
+    /// # fn main() -> InterfaceResult<()> {
+    /// let mut interface = MockInterface::default();
+    /// interface.write_bytes(0x8000_0000, b"GQPE78")?;
+    ///
+    /// let mut game_id = [0; 6];
+    /// interface.read_bytes(0x8000_0000, &mut game_id)?;
+    /// assert_eq!(&game_id, b"GQPE78");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`] if the implementation is unable to access the game, or
+    /// [`InterfaceError::InvalidPointer`] if the range is not within the game's memory.
+    pub fn read_bytes(&self, addr: u32, buf: &mut [u8]) -> InterfaceResult<()> {
+        F::read_bytes(&self.handle, addr, buf)
+    }
+
+    /// Write `bytes` to the game's memory, starting at the GameCube virtual address `addr`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`] if the implementation is unable to access the game, or
+    /// [`InterfaceError::InvalidPointer`] if the range is not within the game's memory.
+    pub fn write_bytes(&mut self, addr: u32, bytes: &[u8]) -> InterfaceResult<()> {
+        F::write_bytes(&self.handle, addr, bytes)
+    }
+
+    /// Follow `ptr`, creating a [`GameVarMut`] for the value it points to.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::game_var::GameVar;
+    /// use bfbb::game_interface::mock::MockInterface;
+    /// use bfbb::game_interface::ptr::GamePtr;
+    /// use bfbb::game_interface::InterfaceResult;
+    ///
+    /// # fn main() -> InterfaceResult<()> {
+    /// let mut interface = MockInterface::default();
+    /// interface.write_bytes(0x8000_1000, &0x8000_2000u32.to_be_bytes())?;
+    /// interface.write_bytes(0x8000_2000, &42u32.to_be_bytes())?;
+    ///
+    /// let ptr = interface.create_var::<GamePtr<u32>>(&[0x8000_1000]).get()?;
+    /// assert_eq!(interface.deref(ptr)?.get()?, 42);
+    /// assert!(interface.deref(GamePtr::<u32>::NULL).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError::InvalidPointer`] if `ptr` is null or does not point into the game's memory.
+    pub fn deref<T: CheckedBitPattern + EndianAware>(
+        &self,
+        ptr: GamePtr<T>,
+    ) -> InterfaceResult<F::Mut<T>> {
+        if !ptr.is_valid() {
+            return Err(InterfaceError::InvalidPointer(ptr.addr()));
+        }
+        Ok(self.create_var(&[ptr.addr() as usize]))
+    }
+
+    /// Create a [`StringVar`] for a string at the location given by the pointer-path `path`, reading at most `max_len`
+    /// bytes.
+    ///
+    /// See [`InterfaceBackend::create_var`] for how `path` is followed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `path` is empty.
+    pub fn string_var(
+        &self,
+        path: impl Into<Vec<usize>>,
+        max_len: usize,
+        encoding: Encoding,
+    ) -> StringVar<F> {
+        StringVar::new(&self.handle, path, max_len, encoding)
+    }
+
+    /// Create a [`DynVar`] of type `ty` at the location given by the pointer-path `path`.
+    ///
+    /// See [`InterfaceBackend::create_var`] for how `path` is followed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `path` is empty.
+    pub fn dyn_var(&self, path: impl Into<Vec<usize>>, ty: DynType) -> DynVar<F> {
+        DynVar::new(&self.handle, path, ty)
+    }
+
+    /// Get the id of the scene that the player is currently in.
+    ///
+    /// Unlike [`get_current_level`](Self::get_current_level), this succeeds for scenes that are not a [`Level`], such
+    /// as menus or unused scenes.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError::DataUnavailable`] sometimes when the game is loading due to the scene pointer being null
+    pub fn get_current_scene(&self) -> InterfaceResult<SceneId> {
+        self.scene_id.get()
+    }
+
     /// Get the level that the player is currently in
     ///
     /// # Errors
     ///
     /// Will return an [`InterfaceError::DataUnavailable`] sometimes when the game is loading due to the scene pointer being null
+    /// or an [`InterfaceError::NotALevel`] if the current scene is not a [`Level`].
     pub fn get_current_level(&self) -> InterfaceResult<Level> {
-        // TODO: It would be great to eventually allow self.scene_id to be of type `F::Var<Level>` directly and be able
-        //       to blanket impl `GameVar<T=Level>` for all backends by using an actual `GameVar<T=[u8;4]>` internally.
-        self.scene_id
-            .get()?
-            .try_into()
-            .map_err(|_| InterfaceError::DataUnavailable)
+        self.current_level().get()
+    }
+
+    /// A [`GameVar`] for the level that the player is currently in, backed by [`scene_id`](Self::scene_id).
+    ///
+    /// Reading it will return an [`InterfaceError::NotALevel`] if the current scene is not a [`Level`].
+    pub fn current_level(&self) -> impl GameVar<Target = Level> + '_ {
+        (&self.scene_id).try_map(|id| Ok(Level::try_from(id)?))
     }
 
     /// Marks a task as available (Silver). This will not update an already unlocked task.
@@ -167,7 +338,7 @@ impl<F: InterfaceBackend> GameInterface<F> {
     ///
     /// Will return an [`InterfaceError`] if the implementation is unable to access the game.
     pub fn mark_task_complete(&mut self, spatula: Spatula) -> InterfaceResult<()> {
-        self.tasks[spatula].menu_count.set(2)
+        self.tasks[spatula].status_mut().set(TaskStatus::Complete)
     }
 
     /// True when `spatula` is shown as gold in the pause menu.
@@ -176,7 +347,7 @@ impl<F: InterfaceBackend> GameInterface<F> {
     ///
     /// Will return an [`InterfaceError`] if the implementation is unable to access the game.
     pub fn is_task_complete(&self, spatula: Spatula) -> InterfaceResult<bool> {
-        Ok(self.tasks[spatula].menu_count.get()? == 2)
+        Ok(self.tasks[spatula].menu_count.get()? == i16::from(TaskStatus::Complete))
     }
 
     /// Collect a spatula in the world. This only removes the entity, it will not complete the task or increment the spatula
@@ -204,17 +375,11 @@ impl<F: InterfaceBackend> GameInterface<F> {
             _ => return Ok(()),
         };
 
-        let mut new_flags = flags.get()?;
-        new_flags &= !1; // Disable the entity
-
-        // Set some model flags
-        let mut new_state = state.get()?;
-        new_state |= 8;
-        new_state &= !4;
-        new_state &= !2;
-
-        flags.set(new_flags)?;
-        state.set(new_state)?;
+        FlagVar::<_, EntityFlags>::new(flags).remove(EntityFlags::ENABLED)?;
+        FlagVar::<_, ModelState>::new(state).update(|state| {
+            state.insert(ModelState::COLLECTED);
+            state.remove(ModelState::COLLECTING | ModelState::AVAILABLE);
+        })?;
         Ok(())
     }
 
@@ -240,7 +405,89 @@ impl<F: InterfaceBackend> GameInterface<F> {
             None => return Ok(false),
         };
 
-        Ok(state.get()? & 4 != 0)
+        FlagVar::<_, ModelState>::new(state).contains(ModelState::COLLECTING)
+    }
+
+    /// Load the progress from a save into the running game, without going through the load menu.
+    ///
+    /// This sets every task's menu counter, the spatula, shiny object and sock counters and the unlocked powers.
+    /// Spatulas of completed tasks in the current level are also collected (see [`collect_spatula`](Self::collect_spatula)).
+    /// The current level and the game's play time are not changed, so the save's [`play_time`](SaveGame::play_time)
+    /// is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::game_var::GameVar;
+    /// use bfbb::game_interface::mock::MockInterface;
+    /// use bfbb::game_interface::InterfaceResult;
+    /// use bfbb::save::SaveGame;
+    /// use bfbb::{Spatula, TaskStatus};
+    ///
+    /// fn main() -> InterfaceResult<()> {
+    ///     let mut save = SaveGame::default();
+    ///     save.tasks.insert(Spatula::SpongebobsCloset, TaskStatus::Complete);
+    ///     save.shiny_objects = 1000;
+    ///
+    ///     let mut interface = MockInterface::default();
+    ///     interface.apply_save(&save)?;
+    ///     assert!(interface.is_task_complete(Spatula::SpongebobsCloset)?);
+    ///     assert_eq!(interface.spatula_count.get()?, 1);
+    ///
+    ///     let dumped = interface.to_save()?;
+    ///     assert_eq!(dumped.tasks, save.tasks);
+    ///     assert_eq!(dumped.shiny_objects, 1000);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`] if the implementation is unable to access the game.
+    pub fn apply_save(&mut self, save: &SaveGame) -> InterfaceResult<()> {
+        let current_level = self.get_current_level().ok();
+        for (&spatula, &status) in &save.tasks {
+            self.tasks[spatula].menu_count.set(status.into())?;
+            if status == TaskStatus::Complete {
+                self.collect_spatula(spatula, current_level)?;
+            }
+        }
+
+        self.spatula_count.set(save.spatula_count())?;
+        self.shiny_count.set(save.shiny_objects)?;
+        self.sock_count.set(save.socks)?;
+        self.powers.bubble_bowl.set(save.powers.bubble_bowl)?;
+        self.powers.cruise_bubble.set(save.powers.cruise_bubble)
+    }
+
+    /// Create a save from the current state of the running game.
+    ///
+    /// The game does not track play time in a way that is accessible to this crate, so the resulting save's
+    /// [`play_time`](SaveGame::play_time) will be zero.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`] if the implementation is unable to access the game, or
+    /// [`InterfaceError::NotALevel`] if the game is not in a level, or [`InterfaceError::DataUnavailable`] if a task's
+    /// counter is invalid.
+    pub fn to_save(&self) -> InterfaceResult<SaveGame> {
+        let tasks = Spatula::iter()
+            .map(|s| {
+                let status = self.tasks[s].status().get()?;
+                Ok((s, status))
+            })
+            .collect::<InterfaceResult<_>>()?;
+
+        Ok(SaveGame {
+            tasks,
+            socks: self.sock_count.get()?,
+            shiny_objects: self.shiny_count.get()?,
+            level: self.get_current_level()?,
+            powers: Powers {
+                bubble_bowl: self.powers.bubble_bowl.get()?,
+                cruise_bubble: self.powers.cruise_bubble.get()?,
+            },
+            play_time: Duration::ZERO,
+        })
     }
 
     /// Changes the number of spatulas required to enter the Chum Bucket Lab.
@@ -303,6 +550,26 @@ impl<F: InterfaceBackend> PowerUps<F> {
     }
 }
 
+impl<F: InterfaceBackend> Task<F> {
+    /// A [`GameVar`] for this task's [`menu_count`](Self::menu_count) as a [`TaskStatus`].
+    ///
+    /// Reading it will return an [`InterfaceError::DataUnavailable`] if the counter is not a valid [`TaskStatus`].
+    pub fn status(&self) -> impl GameVar<Target = TaskStatus> + '_ {
+        (&self.menu_count).try_map(task_status)
+    }
+
+    /// A [`GameVarMut`] for this task's [`menu_count`](Self::menu_count) as a [`TaskStatus`].
+    ///
+    /// Reading it will return an [`InterfaceError::DataUnavailable`] if the counter is not a valid [`TaskStatus`].
+    pub fn status_mut(&mut self) -> impl GameVarMut<Target = TaskStatus> + '_ {
+        (&mut self.menu_count).bidirectional(task_status, i16::from)
+    }
+}
+
+fn task_status(count: i16) -> InterfaceResult<TaskStatus> {
+    TaskStatus::try_from(count).map_err(|_| InterfaceError::DataUnavailable)
+}
+
 impl<F: InterfaceBackend> Hans<F> {
     /// Whether or not Hans is currently enabled.
     ///
@@ -310,7 +577,7 @@ impl<F: InterfaceBackend> Hans<F> {
     ///
     /// Will return a [`InterfaceError`] if the implementation is unable to access the game.
     pub fn is_enabled(&mut self) -> InterfaceResult<bool> {
-        Ok(self.flags.get()? & 4 == 0)
+        Ok(!self.flags().contains(HansFlags::DISABLED)?)
     }
 
     /// Sets Hans' enabled status to `value`
@@ -319,11 +586,7 @@ impl<F: InterfaceBackend> Hans<F> {
     ///
     /// Will return a [`InterfaceError`] if the implementation is unable to access the game.
     pub fn set_enabled(&mut self, value: bool) -> InterfaceResult<()> {
-        let new = match value {
-            true => self.flags.get()? & !4,
-            false => self.flags.get()? | 4,
-        };
-        self.flags.set(new)
+        self.flags().set_flags(HansFlags::DISABLED, !value)
     }
 
     /// Toggles whether Hans is enabled or not
@@ -334,9 +597,12 @@ impl<F: InterfaceBackend> Hans<F> {
     ///
     /// Will return a [`InterfaceError`] if the implementation is unable to access the game.
     pub fn toggle_enabled(&mut self) -> InterfaceResult<bool> {
-        let new = self.flags.get()? ^ 4;
-        self.flags.set(new)?;
-        Ok(new & 4 == 0)
+        let new = self.flags().toggle(HansFlags::DISABLED)?;
+        Ok(!new.contains(HansFlags::DISABLED))
+    }
+
+    fn flags(&mut self) -> FlagVar<&mut F::Mut<u8>, HansFlags> {
+        FlagVar::new(&mut self.flags)
     }
 }
 
@@ -383,6 +649,13 @@ pub trait InterfaceProvider: Default {
     /// *NOTE*: A currently available interface may become unavaiable in the future and vice versa.
     /// For example: The user closes Dolphin, making it unavailable, but then opens it again later.
     fn is_available(&mut self) -> bool;
+
+    /// The build of the game detected when this provider hooked it, if it has.
+    ///
+    /// Providers that don't detect the game's build always return `None`.
+    fn build(&self) -> Option<&Build> {
+        None
+    }
 }
 
 /// Result type for [`GameInterface`] actions.
@@ -410,6 +683,19 @@ pub enum InterfaceError {
     /// Error for when an emulated game is found, but it is not BfBB
     #[error("A game other than SpongeBob SquarePants: Battle for Bikini Bottom is running.")]
     IncorrectGame,
+    /// Error for when BfBB is running, but it is not a build this crate's addresses are known to be correct for
+    /// (e.g. a ROM hack, demo or another region).
+    #[error("Unknown build of the game ({0})")]
+    UnknownBuild(Box<Build>),
+    /// Error for when following a pointer that is null or does not point into the game's memory.
+    #[error("Pointer {0:#010X} does not point into the game's memory")]
+    InvalidPointer(u32),
+    /// Error for when a value written to a [`DynVar`] is not of the variable's type.
+    #[error("Value does not match the variable's type")]
+    TypeMismatch,
+    /// Error for when the current scene is not a [`Level`].
+    #[error(transparent)]
+    NotALevel(#[from] NotALevelError),
     /// Error for when I/O with the interface fails.
     #[error("Unexpected I/O error")]
     Io(std::io::Error),
@@ -0,0 +1,361 @@
+//! Patching the game's executable code.
+//!
+//! A [`Patch`] replaces a run of PowerPC instructions with new ones. Patches are applied through a [`PatchManager`],
+//! which checks that the game's code is what the patch expects before changing it and restores the original
+//! instructions when the patch is reverted or the manager is dropped.
+//!
+//! **NOTE:** Dolphin's JIT caches compiled code, so a patch to code that has already run may not take effect until
+//! Dolphin recompiles it. Patching as early as possible, or using Dolphin's interpreter, avoids this.
+//!
+//! Patches are tied to the [`GameInterface`] their manager was created for. When that interface is dropped, such as
+//! when an [`InterfaceProvider`](super::InterfaceProvider) unhooks or hooks the game again, every patch applied
+//! through it is reverted and its managers fail with [`InterfaceError::Unhooked`] from then on. Create a new manager
+//! after hooking again.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::game_var::GameVar;
+//! use bfbb::game_interface::mock::MockInterface;
+//! use bfbb::game_interface::patch::{Patch, PatchError, PatchManager, PatchResult};
+//! use bfbb::game_interface::InterfaceError;
+//!
+//! # fn main() -> PatchResult<()> {
+//! let mut interface = MockInterface::default();
+//! // `beq 0xC`
+//! interface.write_bytes(0x8000_3100, &0x4182_000Cu32.to_be_bytes())?;
+//!
+//! let mut patches = PatchManager::new(&interface);
+//! // Replace the branch with a `nop`
+//! patches.apply(Patch::new("Skip check", 0x8000_3100, &[0x4182_000C], &[0x6000_0000]))?;
+//! assert!(patches.is_active("Skip check"));
+//!
+//! let mut code = [0; 4];
+//! interface.read_bytes(0x8000_3100, &mut code)?;
+//! assert_eq!(u32::from_be_bytes(code), 0x6000_0000);
+//!
+//! patches.revert("Skip check")?;
+//! interface.read_bytes(0x8000_3100, &mut code)?;
+//! assert_eq!(u32::from_be_bytes(code), 0x4182_000C);
+//!
+//! // Patches can't wrap around the end of the address space
+//! let patch = Patch::new("Wrap", 0xFFFF_FFFC, &[0, 0], &[0, 0]);
+//! assert!(matches!(patches.apply(patch), Err(PatchError::OutOfRange(_))));
+//!
+//! // Unhooking reverts the remaining patches
+//! let code = interface.create_var::<u32>(&[0x8000_3100]);
+//! patches.apply(Patch::new("Skip check", 0x8000_3100, &[0x4182_000C], &[0x6000_0000]))?;
+//! drop(interface);
+//! assert_eq!(code.get()?, 0x4182_000C);
+//! assert!(!patches.is_active("Skip check"));
+//! assert!(matches!(
+//!     patches.apply(Patch::new("Skip check", 0x8000_3100, &[0x4182_000C], &[0x6000_0000])),
+//!     Err(PatchError::Interface(InterfaceError::Unhooked))
+//! ));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use thiserror::Error;
+
+use super::{game_var::InterfaceBackend, GameInterface, InterfaceError};
+
+/// Result type for applying or reverting [`Patch`]es.
+pub type PatchResult<T> = std::result::Result<T, PatchError>;
+
+/// Error type for failures to apply or revert a [`Patch`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PatchError {
+    /// A patch with the same name is already applied.
+    #[error("Patch '{0}' is already applied")]
+    AlreadyApplied(String),
+    /// There is no applied patch with the given name.
+    #[error("Patch '{0}' is not applied")]
+    NotApplied(String),
+    /// A patch extends past the end of the address space.
+    #[error("Patch '{0}' extends past the end of memory")]
+    OutOfRange(String),
+    /// A patch would change code already changed by another applied patch.
+    #[error("Patch '{patch}' overlaps applied patch '{applied}'")]
+    Overlaps {
+        /// The name of the patch being applied.
+        patch: String,
+        /// The name of the applied patch it overlaps.
+        applied: String,
+    },
+    /// The game's code is not what the patch expects, so changing it is not safe.
+    ///
+    /// When applying, the code does not match the patch's original instructions (e.g. a different version of the
+    /// game). When reverting, it matches neither the original nor the patched instructions (e.g. it has been
+    /// overwritten by something else).
+    #[error("Code at {addr:#010X} does not match patch '{patch}'")]
+    UnexpectedCode {
+        /// The name of the patch.
+        patch: String,
+        /// The address of the patch.
+        addr: u32,
+    },
+    /// The game could not be accessed.
+    #[error(transparent)]
+    Interface(#[from] InterfaceError),
+}
+
+/// A named replacement of PowerPC instructions at an address in the game's code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Patch {
+    name: String,
+    addr: u32,
+    original: Vec<u8>,
+    patched: Vec<u8>,
+}
+
+impl Patch {
+    /// Create a patch named `name` that replaces the instructions `original` at `addr` with `patched`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `original` and `patched` are not the same length.
+    pub fn new(name: impl Into<String>, addr: u32, original: &[u32], patched: &[u32]) -> Self {
+        assert_eq!(
+            original.len(),
+            patched.len(),
+            "A patch must replace the same number of instructions"
+        );
+        let bytes = |code: &[u32]| code.iter().flat_map(|i| i.to_be_bytes()).collect();
+        Self {
+            name: name.into(),
+            addr,
+            original: bytes(original),
+            patched: bytes(patched),
+        }
+    }
+
+    /// The name of this patch.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The address of the first instruction this patch replaces.
+    #[must_use]
+    pub fn addr(&self) -> u32 {
+        self.addr
+    }
+
+    /// The address after the last instruction this patch replaces, or `None` if that would overflow.
+    fn end(&self) -> Option<u32> {
+        u32::try_from(self.original.len())
+            .ok()
+            .and_then(|len| self.addr.checked_add(len))
+    }
+}
+
+/// Applies [`Patch`]es to the game and keeps track of them so they can be reverted.
+///
+/// Every applied patch is reverted when the manager is dropped. Failures to revert at that point are logged.
+///
+/// Patches are also reverted when the [`GameInterface`] the manager was created for is dropped, after which the
+/// manager fails with [`InterfaceError::Unhooked`]. See the [module documentation](self).
+pub struct PatchManager<F: InterfaceBackend> {
+    handle: F::Handle,
+    state: Arc<Mutex<PatchState>>,
+}
+
+/// The patches applied by a [`PatchManager`], shared with the [`GameInterface`] it was created for.
+#[derive(Default)]
+struct PatchState {
+    applied: Vec<Patch>,
+    /// Set once the interface has been dropped and every patch reverted.
+    unhooked: bool,
+}
+
+/// The state of every [`PatchManager`] created for a [`GameInterface`], so their patches can be reverted when it is
+/// dropped.
+#[derive(Default)]
+pub(crate) struct PatchRegistry {
+    managers: Mutex<Vec<Weak<Mutex<PatchState>>>>,
+}
+
+impl PatchRegistry {
+    /// Revert every patch applied by managers of this registry and mark them as unhooked.
+    ///
+    /// The game may already be gone, so failures are only logged.
+    pub(crate) fn revert_all<F: InterfaceBackend>(&self, handle: &F::Handle) {
+        let managers = std::mem::take(&mut *lock(&self.managers));
+        for state in managers.iter().filter_map(Weak::upgrade) {
+            let mut state = lock(&state);
+            state.unhooked = true;
+            for patch in state.applied.drain(..).rev() {
+                match revert_patch::<F>(handle, &patch) {
+                    Ok(()) => {}
+                    // The game has closed, taking the patch with it
+                    Err(PatchError::Interface(InterfaceError::Unhooked)) => {
+                        log::debug!("Dropped patch '{}' of an unhooked game", patch.name);
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Failed to revert patch '{}' when unhooking: {e}",
+                            patch.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().expect("Patch lock should not be poisoned")
+}
+
+fn read_patch<F: InterfaceBackend>(handle: &F::Handle, patch: &Patch) -> PatchResult<Vec<u8>> {
+    let mut current = vec![0; patch.original.len()];
+    F::read_bytes(handle, patch.addr, &mut current)?;
+    Ok(current)
+}
+
+/// Restore the original instructions of `patch`, unless the game's code has already been restored.
+fn revert_patch<F: InterfaceBackend>(handle: &F::Handle, patch: &Patch) -> PatchResult<()> {
+    let current = read_patch::<F>(handle, patch)?;
+    if current == patch.patched {
+        F::write_bytes(handle, patch.addr, &patch.original)?;
+    } else if current != patch.original {
+        return Err(PatchError::UnexpectedCode {
+            patch: patch.name.clone(),
+            addr: patch.addr,
+        });
+    }
+    log::debug!("Reverted patch '{}' at {:#010X}", patch.name, patch.addr);
+    Ok(())
+}
+
+impl<F: InterfaceBackend> PatchManager<F> {
+    /// Create a new manager for the game accessed by `interface`, with no patches applied.
+    pub fn new(interface: &GameInterface<F>) -> Self {
+        let state = Arc::default();
+        lock(&interface.patches.managers).push(Arc::downgrade(&state));
+        Self {
+            handle: interface.handle.clone(),
+            state,
+        }
+    }
+
+    /// Apply `patch`, after checking that the game's code matches its original instructions.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`PatchError`] if a patch with the same name is already applied, it extends past the end of
+    /// memory, it overlaps another applied patch, the game's code does not match, or the implementation is unable to
+    /// access the game. This is [`InterfaceError::Unhooked`] once the interface this manager was created for has been
+    /// dropped.
+    pub fn apply(&mut self, patch: Patch) -> PatchResult<()> {
+        let mut state = self.lock()?;
+        if state.applied.iter().any(|p| p.name == patch.name) {
+            return Err(PatchError::AlreadyApplied(patch.name));
+        }
+        let Some(end) = patch.end() else {
+            return Err(PatchError::OutOfRange(patch.name));
+        };
+        if let Some(applied) = state
+            .applied
+            .iter()
+            .find(|p| p.addr < end && p.end().map_or(false, |p_end| patch.addr < p_end))
+        {
+            return Err(PatchError::Overlaps {
+                patch: patch.name,
+                applied: applied.name.clone(),
+            });
+        }
+
+        if read_patch::<F>(&self.handle, &patch)? != patch.original {
+            return Err(PatchError::UnexpectedCode {
+                patch: patch.name,
+                addr: patch.addr,
+            });
+        }
+        F::write_bytes(&self.handle, patch.addr, &patch.patched)?;
+        log::debug!("Applied patch '{}' at {:#010X}", patch.name, patch.addr);
+        state.applied.push(patch);
+        Ok(())
+    }
+
+    /// Revert the patch named `name`, restoring the original instructions.
+    ///
+    /// If the game's code has already been restored (e.g. because the game was restarted) the patch is simply
+    /// forgotten.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`PatchError`] if no patch named `name` is applied, the game's code matches neither the
+    /// original nor the patched instructions, or the implementation is unable to access the game. The patch remains
+    /// applied if an error is returned.
+    pub fn revert(&mut self, name: &str) -> PatchResult<()> {
+        let mut state = self.lock()?;
+        let i = state
+            .applied
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| PatchError::NotApplied(name.to_owned()))?;
+        revert_patch::<F>(&self.handle, &state.applied[i])?;
+        state.applied.remove(i);
+        Ok(())
+    }
+
+    /// Revert every applied patch, most recently applied first.
+    ///
+    /// # Errors
+    ///
+    /// Will return the first [`PatchError`] encountered. Patches that could not be reverted remain applied.
+    pub fn revert_all(&mut self) -> PatchResult<()> {
+        let names: Vec<_> = self
+            .lock()?
+            .applied
+            .iter()
+            .rev()
+            .map(|p| p.name.clone())
+            .collect();
+        let mut result = Ok(());
+        for name in names {
+            if let Err(e) = self.revert(&name) {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns `true` if a patch named `name` is applied.
+    #[must_use]
+    pub fn is_active(&self, name: &str) -> bool {
+        lock(&self.state).applied.iter().any(|p| p.name == name)
+    }
+
+    /// The applied patches, in the order they were applied.
+    #[must_use]
+    pub fn active(&self) -> Vec<Patch> {
+        lock(&self.state).applied.clone()
+    }
+
+    /// Lock this manager's state, failing if the interface it was created for has been dropped.
+    fn lock(&self) -> PatchResult<MutexGuard<'_, PatchState>> {
+        let state = lock(&self.state);
+        if state.unhooked {
+            return Err(InterfaceError::Unhooked.into());
+        }
+        Ok(state)
+    }
+}
+
+impl<F: InterfaceBackend> Drop for PatchManager<F> {
+    fn drop(&mut self) {
+        if lock(&self.state).unhooked {
+            return;
+        }
+        if let Err(e) = self.revert_all() {
+            log::warn!("Failed to revert patches: {e}");
+        }
+    }
+}
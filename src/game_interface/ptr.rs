@@ -0,0 +1,200 @@
+//! Typed pointers into the game's memory.
+//!
+//! A [`GamePtr`] is a pointer as stored by the game. It can be read like any other variable and then followed at
+//! runtime, which allows walking linked structures without knowing their layout ahead of time.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::game_var::{GameVar, InterfaceBackend};
+//! use bfbb::game_interface::ptr::GamePtr;
+//! use bfbb::game_interface::{GameInterface, InterfaceResult};
+//!
+//! /// Count the nodes of a singly linked list whose `next` pointer is the first field of each node.
+//! fn list_len<F: InterfaceBackend>(
+//!     interface: &GameInterface<F>,
+//!     head: GamePtr<GamePtr<()>>,
+//! ) -> InterfaceResult<usize> {
+//!     let mut node = head;
+//!     let mut len = 0;
+//!     while !node.is_null() {
+//!         len += 1;
+//!         node = interface.deref(node)?.get()?.cast();
+//!     }
+//!     Ok(len)
+//! }
+//! ```
+
+use std::{fmt::Debug, marker::PhantomData};
+
+use bytemuck::{CheckedBitPattern, Pod, Zeroable};
+
+use crate::endian::EndianAware;
+
+use super::{game_var::InterfaceBackend, InterfaceError, InterfaceResult};
+
+/// The first address of the GameCube's main memory.
+pub const MEMORY_START: u32 = 0x8000_0000;
+/// The address one past the end of the GameCube's main memory (24 MiB).
+pub const MEMORY_END: u32 = 0x8180_0000;
+
+/// A pointer to a `T` within the game's memory, as stored by the game.
+///
+/// This can be read from the game as a [`GameVar`](super::game_var::GameVar), and followed with
+/// [`GameInterface::deref`](super::GameInterface::deref).
+#[repr(transparent)]
+pub struct GamePtr<T> {
+    addr: u32,
+    _target: PhantomData<fn() -> T>,
+}
+
+impl<T> GamePtr<T> {
+    /// A null pointer.
+    pub const NULL: Self = Self::new(0);
+
+    /// Create a pointer to the virtual address `addr`.
+    #[must_use]
+    pub const fn new(addr: u32) -> Self {
+        Self {
+            addr,
+            _target: PhantomData,
+        }
+    }
+
+    /// The virtual address this pointer points to.
+    #[must_use]
+    pub const fn addr(self) -> u32 {
+        self.addr
+    }
+
+    /// Returns `true` if this pointer is null.
+    #[must_use]
+    pub const fn is_null(self) -> bool {
+        self.addr == 0
+    }
+
+    /// Returns `true` if this pointer points within the GameCube's main memory and can therefore be followed.
+    #[must_use]
+    pub const fn is_valid(self) -> bool {
+        self.addr >= MEMORY_START && self.addr < MEMORY_END
+    }
+
+    /// Reinterpret this as a pointer to a different type.
+    #[must_use]
+    pub const fn cast<U>(self) -> GamePtr<U> {
+        GamePtr::new(self.addr)
+    }
+
+    /// Get a pointer to a `U` located `offset` bytes past this pointer, such as a field of the pointed to struct.
+    #[must_use]
+    pub const fn offset<U>(self, offset: u32) -> GamePtr<U> {
+        GamePtr::new(self.addr.wrapping_add(offset))
+    }
+}
+
+impl<T> Clone for GamePtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GamePtr<T> {}
+
+impl<T> PartialEq for GamePtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr == other.addr
+    }
+}
+
+impl<T> Eq for GamePtr<T> {}
+
+impl<T> std::hash::Hash for GamePtr<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.addr.hash(state);
+    }
+}
+
+impl<T> Debug for GamePtr<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GamePtr({:#010X})", self.addr)
+    }
+}
+
+impl<T> Default for GamePtr<T> {
+    fn default() -> Self {
+        Self::NULL
+    }
+}
+
+// SAFETY: `GamePtr` is a `repr(transparent)` wrapper of a `u32`, `PhantomData` is a ZST with no invalid bit patterns.
+unsafe impl<T> Zeroable for GamePtr<T> {}
+// SAFETY: As above, and `PhantomData<fn() -> T>` is always `'static` when `T` is.
+unsafe impl<T: 'static> Pod for GamePtr<T> {}
+
+impl<T> EndianAware for GamePtr<T> {
+    fn swap_endian(bytes: &mut [u8]) {
+        u32::swap_endian(bytes);
+    }
+}
+
+/// Fail with [`InterfaceError::InvalidPointer`] unless `len` bytes at `addr` are within the game's memory.
+///
+/// Every backend uses this bound, so that accesses which succeed on one succeed on all of them.
+pub(crate) fn check_range(addr: u32, len: usize) -> InterfaceResult<()> {
+    let end = u32::try_from(len)
+        .ok()
+        .and_then(|len| addr.checked_add(len));
+    match end {
+        Some(end) if addr >= MEMORY_START && end <= MEMORY_END => Ok(()),
+        _ => Err(InterfaceError::InvalidPointer(addr)),
+    }
+}
+
+/// Follow the pointer-path `path` to the address of the variable it locates, as described by
+/// [`InterfaceBackend::create_var`].
+pub(crate) fn resolve_path<F: InterfaceBackend>(
+    handle: &F::Handle,
+    path: &[usize],
+) -> InterfaceResult<u32> {
+    let (&last, pointers) = path.split_last().ok_or(InterfaceError::InvalidPointer(0))?;
+    let offset = |base: u32, offset: usize| {
+        u32::try_from(offset)
+            .ok()
+            .and_then(|offset| base.checked_add(offset))
+            .ok_or(InterfaceError::InvalidPointer(base))
+    };
+
+    let mut addr = 0;
+    for &next in pointers {
+        let mut ptr = [0; 4];
+        F::read_bytes(handle, offset(addr, next)?, &mut ptr)?;
+        addr = u32::from_be_bytes(ptr);
+    }
+    offset(addr, last)
+}
+
+/// Read the `T` at the location given by `path`.
+pub(crate) fn read_value<F: InterfaceBackend, T: CheckedBitPattern + EndianAware>(
+    handle: &F::Handle,
+    path: &[usize],
+) -> InterfaceResult<T> {
+    let mut bytes = vec![0; std::mem::size_of::<T>()];
+    F::read_bytes(handle, resolve_path::<F>(handle, path)?, &mut bytes)?;
+    T::swap_endian(&mut bytes);
+    bytemuck::checked::try_pod_read_unaligned(&bytes)
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidData).into())
+}
+
+/// Write `value` to the location given by `path`.
+pub(crate) fn write_value<F: InterfaceBackend, T: CheckedBitPattern + EndianAware>(
+    handle: &F::Handle,
+    path: &[usize],
+    value: &T,
+) -> InterfaceResult<()> {
+    // This assumes game types have no padding bytes
+    let bytes = unsafe {
+        std::slice::from_raw_parts((value as *const T).cast::<u8>(), std::mem::size_of::<T>())
+    };
+    let mut bytes = bytes.to_vec();
+    T::swap_endian(&mut bytes);
+    F::write_bytes(handle, resolve_path::<F>(handle, path)?, &bytes)
+}
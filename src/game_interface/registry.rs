@@ -0,0 +1,319 @@
+//! Variables defined by configuration files instead of code.
+//!
+//! A [`VarDefs`] is a list of variable definitions read from a TOML or JSON file, each with a name, a
+//! [`DynType`], a pointer-path and whether it may be written to. A [`Registry`] creates a [`DynVar`] for each
+//! definition on any backend, which can then be read and written by name.
+//!
+//! In TOML, each variable is a `[[var]]` table. Offsets of the path may be written as numbers or hexadecimal strings:
+//!
+//! ```toml
+//! [[var]]
+//! name = "spatula_count"
+//! type = "u32"
+//! path = [0x803C205C]
+//! writable = true
+//!
+//! [[var]]
+//! name = "scene_id"
+//! type = "string[4]"
+//! path = ["803C2518", 0]
+//! region = "GQPE78"
+//! ```
+//!
+//! JSON files have the same structure, with a `var` array of objects.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::dynamic::DynValue;
+//! use bfbb::game_interface::mock::MockInterface;
+//! use bfbb::game_interface::registry::{Registry, RegistryError, VarDefs};
+//!
+//! # fn main() -> Result<(), RegistryError> {
+//! let defs = VarDefs::from_toml(
+//!     r#"
+//!     [[var]]
+//!     name = "spatula_count"
+//!     type = "u32"
+//!     path = [0x803C205C]
+//!     writable = true
+//!
+//!     [[var]]
+//!     name = "is_loading"
+//!     type = "u8"
+//!     path = [0x803CB7B3]
+//!     "#,
+//! )?;
+//!
+//! let mut interface = MockInterface::default();
+//! let mut registry = Registry::new(&defs, &interface, None)?;
+//! registry.set("spatula_count", DynValue::U32(75))?;
+//! assert_eq!(registry.get("spatula_count")?, DynValue::U32(75));
+//!
+//! let mut bytes = [0; 4];
+//! interface.read_bytes(0x803C_205C, &mut bytes)?;
+//! assert_eq!(bytes, 75u32.to_be_bytes());
+//!
+//! interface.write_bytes(0x803C_B7B3, &[1])?;
+//! assert_eq!(registry.get("is_loading")?, DynValue::U8(1));
+//! assert!(matches!(
+//!     registry.set("is_loading", DynValue::U8(0)),
+//!     Err(RegistryError::ReadOnly(_))
+//! ));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    dynamic::{DynType, DynValue, DynVar},
+    game_var::{GameVar, GameVarMut, InterfaceBackend},
+    GameInterface, InterfaceError,
+};
+
+/// Result type for loading and accessing a [`Registry`].
+pub type RegistryResult<T> = std::result::Result<T, RegistryError>;
+
+/// Error type for failures to load or access a [`Registry`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RegistryError {
+    /// A definitions file could not be read from disk.
+    #[error("Definitions could not be read")]
+    Io(#[from] std::io::Error),
+    /// A definitions file is neither a `.toml` nor a `.json` file.
+    #[error("Definitions must be a .toml or .json file")]
+    UnknownFormat,
+    /// The definitions are not valid TOML.
+    #[error("Invalid TOML definitions")]
+    Toml(#[from] toml::de::Error),
+    /// The definitions are not valid JSON.
+    #[error("Invalid JSON definitions")]
+    Json(#[from] serde_json::Error),
+    /// A variable's type is not a valid [`DynType`].
+    #[error("Variable '{name}' has invalid type '{ty}'")]
+    InvalidType {
+        /// The name of the variable.
+        name: String,
+        /// The type as written in the definitions.
+        ty: String,
+    },
+    /// A variable's path is empty or has an offset that is not a number.
+    #[error("Variable '{0}' has an invalid path")]
+    InvalidPath(String),
+    /// Two variables have the same name.
+    #[error("Variable '{0}' is defined more than once")]
+    Duplicate(String),
+    /// There is no variable with the given name.
+    #[error("No variable named '{0}'")]
+    Unknown(String),
+    /// The variable is not writable.
+    #[error("Variable '{0}' is not writable")]
+    ReadOnly(String),
+    /// The game could not be accessed.
+    #[error(transparent)]
+    Interface(#[from] InterfaceError),
+}
+
+/// The definition of a variable in a [`Registry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VarDef {
+    /// The name the variable is accessed by.
+    pub name: String,
+    /// The type of the variable.
+    pub ty: DynType,
+    /// The pointer-path of the variable, in the same form as [`InterfaceBackend::create_var`].
+    pub path: Vec<usize>,
+    /// Whether the variable may be written to. Defaults to `false`.
+    pub writable: bool,
+    /// The game code (e.g. `GQPE78`) of the only release this variable exists in, or `None` for every release.
+    pub region: Option<String>,
+}
+
+/// A list of variable definitions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VarDefs {
+    /// The definitions.
+    pub vars: Vec<VarDef>,
+}
+
+impl VarDefs {
+    /// Read definitions from a `.toml` or `.json` file, depending on its extension.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`RegistryError`] if the file can not be read or does not contain valid definitions.
+    pub fn open(path: impl AsRef<Path>) -> RegistryResult<Self> {
+        let path = path.as_ref();
+        let ext = path.extension().and_then(|e| e.to_str());
+        match ext.map(str::to_ascii_lowercase).as_deref() {
+            Some("toml") => Self::from_toml(&std::fs::read_to_string(path)?),
+            Some("json") => Self::from_json(&std::fs::read_to_string(path)?),
+            _ => Err(RegistryError::UnknownFormat),
+        }
+    }
+
+    /// Read definitions from TOML.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`RegistryError`] if `toml` does not contain valid definitions.
+    pub fn from_toml(toml: &str) -> RegistryResult<Self> {
+        toml::from_str::<RawDefs>(toml)?.try_into()
+    }
+
+    /// Read definitions from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`RegistryError`] if `json` does not contain valid definitions.
+    pub fn from_json(json: &str) -> RegistryResult<Self> {
+        serde_json::from_str::<RawDefs>(json)?.try_into()
+    }
+}
+
+/// Variables created from [`VarDefs`], accessible by name.
+pub struct Registry<F: InterfaceBackend> {
+    vars: BTreeMap<String, (VarDef, DynVar<F>)>,
+}
+
+impl<F: InterfaceBackend> Registry<F> {
+    /// Create a variable for every definition in `defs` on the game accessed by `interface`.
+    ///
+    /// Definitions for a specific region are only included if it is `region`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`RegistryError::Duplicate`] if two included definitions have the same name.
+    pub fn new(
+        defs: &VarDefs,
+        interface: &GameInterface<F>,
+        region: Option<&str>,
+    ) -> RegistryResult<Self> {
+        let mut vars = BTreeMap::new();
+        for def in defs
+            .vars
+            .iter()
+            .filter(|d| d.region.is_none() || d.region.as_deref() == region)
+        {
+            if vars.contains_key(&def.name) {
+                return Err(RegistryError::Duplicate(def.name.clone()));
+            }
+            let var = interface.dyn_var(def.path.clone(), def.ty);
+            vars.insert(def.name.clone(), (def.clone(), var));
+        }
+        Ok(Self { vars })
+    }
+
+    /// Read the variable named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`RegistryError`] if there is no such variable or the implementation is unable to access the
+    /// game.
+    pub fn get(&self, name: &str) -> RegistryResult<DynValue> {
+        let (_, var) = self
+            .vars
+            .get(name)
+            .ok_or_else(|| RegistryError::Unknown(name.to_owned()))?;
+        Ok(var.get()?)
+    }
+
+    /// Write `value` to the variable named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a [`RegistryError`] if there is no such variable, it is not writable, `value` is not of its type,
+    /// or the implementation is unable to access the game.
+    pub fn set(&mut self, name: &str, value: DynValue) -> RegistryResult<()> {
+        let (def, var) = self
+            .vars
+            .get_mut(name)
+            .ok_or_else(|| RegistryError::Unknown(name.to_owned()))?;
+        if !def.writable {
+            return Err(RegistryError::ReadOnly(name.to_owned()));
+        }
+        Ok(var.set(value)?)
+    }
+
+    /// The definition of the variable named `name`.
+    #[must_use]
+    pub fn def(&self, name: &str) -> Option<&VarDef> {
+        self.vars.get(name).map(|(def, _)| def)
+    }
+
+    /// The names of every variable, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.vars.keys().map(String::as_str)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawDefs {
+    #[serde(default, rename = "var")]
+    vars: Vec<RawDef>,
+}
+
+#[derive(Deserialize)]
+struct RawDef {
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    path: Vec<RawOffset>,
+    #[serde(default)]
+    writable: bool,
+    #[serde(default)]
+    region: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawOffset {
+    Number(usize),
+    Hex(String),
+}
+
+impl TryFrom<RawDefs> for VarDefs {
+    type Error = RegistryError;
+
+    fn try_from(raw: RawDefs) -> Result<Self, Self::Error> {
+        let vars = raw
+            .vars
+            .into_iter()
+            .map(|def| {
+                let Ok(ty) = def.ty.parse() else {
+                    return Err(RegistryError::InvalidType {
+                        name: def.name,
+                        ty: def.ty,
+                    });
+                };
+                let path = def
+                    .path
+                    .iter()
+                    .map(|offset| match offset {
+                        RawOffset::Number(x) => Some(*x),
+                        RawOffset::Hex(s) => {
+                            let s = s.trim();
+                            usize::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16).ok()
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|p| !p.is_empty());
+                let Some(path) = path else {
+                    return Err(RegistryError::InvalidPath(def.name));
+                };
+                Ok(VarDef {
+                    name: def.name,
+                    ty,
+                    path,
+                    writable: def.writable,
+                    region: def.region,
+                })
+            })
+            .collect::<RegistryResult<_>>()?;
+        Ok(Self { vars })
+    }
+}
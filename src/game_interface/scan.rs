@@ -0,0 +1,318 @@
+//! Searching the game's memory for values, in the style of Cheat Engine or Dolphin Memory Engine.
+//!
+//! A scan starts by searching the whole of the GameCube's main memory for a value (or every value, when it is
+//! unknown), and is then narrowed down over successive scans by how the values have changed since the previous scan.
+//! The surviving addresses can be turned into [`GameVar`](super::game_var::GameVar)s.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::game_var::GameVar;
+//! use bfbb::game_interface::mock::MockInterface;
+//! use bfbb::game_interface::scan::{NextScan, ScanOptions, Scanner, FirstScan};
+//! use bfbb::game_interface::InterfaceResult;
+//!
+//! # fn main() -> InterfaceResult<()> {
+//! let mut interface = MockInterface::default();
+//! interface.write_bytes(0x8000_1000, &20u32.to_be_bytes())?;
+//! interface.write_bytes(0x8000_2000, &20u32.to_be_bytes())?;
+//!
+//! // Only scan a small region, as the mock backend is slow to read large amounts of memory
+//! let options = ScanOptions {
+//!     region: 0x8000_0000..0x8001_0000,
+//!     ..ScanOptions::default()
+//! };
+//! let mut scanner = Scanner::<u32>::first_scan(&interface, FirstScan::Exact(20), options.clone())?;
+//! assert_eq!(scanner.len(), 2);
+//!
+//! // The value we're looking for went up
+//! interface.write_bytes(0x8000_2000, &21u32.to_be_bytes())?;
+//! scanner.next_scan(&interface, NextScan::Increased)?;
+//! assert_eq!(scanner.results().collect::<Vec<_>>(), [(0x8000_2000, 21)]);
+//!
+//! let vars: Vec<_> = scanner.vars(&interface).collect();
+//! assert_eq!(vars.len(), 1);
+//! assert_eq!(vars[0].get()?, 21);
+//!
+//! // Search for a value without knowing what it is, only how it changes
+//! let mut scanner = Scanner::<u32>::first_scan(&interface, FirstScan::Unknown, options)?;
+//! assert_eq!(scanner.len(), 0x1_0000 / 4);
+//! interface.write_bytes(0x8000_3000, &5u32.to_be_bytes())?;
+//! scanner.next_scan(&interface, NextScan::Changed)?;
+//! assert_eq!(scanner.results().collect::<Vec<_>>(), [(0x8000_3000, 5)]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::ops::Range;
+
+use bytemuck::CheckedBitPattern;
+
+use crate::endian::EndianAware;
+
+use super::{
+    game_var::InterfaceBackend,
+    ptr::{MEMORY_END, MEMORY_START},
+    GameInterface, InterfaceResult,
+};
+
+/// The number of bytes read from the game at once when scanning.
+const CHUNK_SIZE: u32 = 0x1_0000;
+
+/// A type that can be searched for with a [`Scanner`].
+pub trait Scannable: CheckedBitPattern + EndianAware + PartialOrd + Copy {}
+impl<T: CheckedBitPattern + EndianAware + PartialOrd + Copy> Scannable for T {}
+
+/// The filter for the first scan of a [`Scanner`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FirstScan<T> {
+    /// Values equal to this value.
+    Exact(T),
+    /// Values between these values, inclusive.
+    Range(T, T),
+    /// Every value. Useful when only the way the value changes is known.
+    Unknown,
+}
+
+/// The filter for each scan after the first of a [`Scanner`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NextScan<T> {
+    /// Values now equal to this value.
+    Exact(T),
+    /// Values now between these values, inclusive.
+    Range(T, T),
+    /// Values different from the previous scan.
+    Changed,
+    /// Values the same as the previous scan.
+    Unchanged,
+    /// Values greater than the previous scan.
+    Increased,
+    /// Values less than the previous scan.
+    Decreased,
+}
+
+/// Options for the first scan of a [`Scanner`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// The range of GameCube virtual addresses to search. Defaults to all of main memory.
+    pub region: Range<u32>,
+    /// The alignment of addresses to search. Defaults to the alignment of the scanned type.
+    pub alignment: Option<u32>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            region: MEMORY_START..MEMORY_END,
+            alignment: None,
+        }
+    }
+}
+
+/// A search of the game's memory for values of type `T`.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct Scanner<T> {
+    state: State<T>,
+}
+
+#[derive(Debug, Clone)]
+enum State<T> {
+    /// After a [`FirstScan::Unknown`] every aligned address is a result, so a copy of the region is kept instead.
+    Snapshot {
+        start: u32,
+        align: u32,
+        bytes: Vec<u8>,
+        len: usize,
+    },
+    Results(Vec<(u32, T)>),
+}
+
+impl<T: Scannable> Scanner<T> {
+    /// Start a new scan, searching `options.region` for values matching `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn first_scan<F: InterfaceBackend>(
+        interface: &GameInterface<F>,
+        filter: FirstScan<T>,
+        options: ScanOptions,
+    ) -> InterfaceResult<Self> {
+        let align = options
+            .alignment
+            .unwrap_or(std::mem::align_of::<T>() as u32)
+            .max(1);
+        let region = options.region;
+        let bytes = read_region(interface, region.clone())?;
+        let values = snapshot_values::<T>(region.start, align, &bytes);
+
+        let state = match filter {
+            FirstScan::Unknown => State::Snapshot {
+                start: region.start,
+                align,
+                len: values.count(),
+                bytes,
+            },
+            FirstScan::Exact(x) => State::Results(values.filter(|&(_, v)| v == x).collect()),
+            FirstScan::Range(min, max) => {
+                State::Results(values.filter(|&(_, v)| min <= v && v <= max).collect())
+            }
+        };
+        Ok(Self { state })
+    }
+
+    /// Narrow down the results of the previous scan to those matching `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn next_scan<F: InterfaceBackend>(
+        &mut self,
+        interface: &GameInterface<F>,
+        filter: NextScan<T>,
+    ) -> InterfaceResult<()> {
+        let matches = |value: T, previous: T| match filter {
+            NextScan::Exact(x) => value == x,
+            NextScan::Range(min, max) => min <= value && value <= max,
+            NextScan::Changed => value != previous,
+            NextScan::Unchanged => value == previous,
+            NextScan::Increased => value > previous,
+            NextScan::Decreased => value < previous,
+        };
+
+        match &mut self.state {
+            State::Snapshot {
+                start,
+                align,
+                bytes,
+                ..
+            } => {
+                let end = *start + bytes.len() as u32;
+                let current = read_region(interface, *start..end)?;
+                let results = aligned_values::<T>(*start, *align, bytes)
+                    .zip(aligned_values::<T>(*start, *align, &current))
+                    .filter_map(|((_, previous), (addr, value))| {
+                        let value = value?;
+                        matches(value, previous?).then_some((addr, value))
+                    })
+                    .collect();
+                self.state = State::Results(results);
+            }
+            State::Results(results) => {
+                let size = std::mem::size_of::<T>() as u32;
+                let mut kept = Vec::new();
+                // Read nearby results together, without reading the whole span between the first and last result
+                for group in group_results(results, size) {
+                    let start = group[0].0;
+                    let end = group[group.len() - 1].0 + size;
+                    let mut buf = vec![0; (end - start) as usize];
+                    interface.read_bytes(start, &mut buf)?;
+                    kept.extend(group.iter().filter_map(|&(addr, previous)| {
+                        let offset = (addr - start) as usize;
+                        let value = decode::<T>(&buf[offset..offset + size as usize])?;
+                        matches(value, previous).then_some((addr, value))
+                    }));
+                }
+                *results = kept;
+            }
+        }
+        Ok(())
+    }
+
+    /// The address and value (as of the last scan) of every result, in ascending order of address.
+    pub fn results(&self) -> Box<dyn Iterator<Item = (u32, T)> + '_> {
+        match &self.state {
+            State::Snapshot {
+                start,
+                align,
+                bytes,
+                ..
+            } => Box::new(snapshot_values(*start, *align, bytes)),
+            State::Results(results) => Box::new(results.iter().copied()),
+        }
+    }
+
+    /// The number of results remaining.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &self.state {
+            State::Snapshot { len, .. } => *len,
+            State::Results(results) => results.len(),
+        }
+    }
+
+    /// Returns `true` if no results remain.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Create a [`GameVarMut`](super::game_var::GameVarMut) for each remaining result.
+    pub fn vars<'a, F: InterfaceBackend>(
+        &'a self,
+        interface: &'a GameInterface<F>,
+    ) -> impl Iterator<Item = F::Mut<T>> + 'a {
+        self.results()
+            .map(|(addr, _)| interface.create_var(&[addr as usize]))
+    }
+}
+
+/// Read all of `region`, in chunks of at most [`CHUNK_SIZE`] bytes.
+fn read_region<F: InterfaceBackend>(
+    interface: &GameInterface<F>,
+    region: Range<u32>,
+) -> InterfaceResult<Vec<u8>> {
+    let mut bytes = vec![0; region.len()];
+    for (i, chunk) in bytes.chunks_mut(CHUNK_SIZE as usize).enumerate() {
+        interface.read_bytes(region.start + i as u32 * CHUNK_SIZE, chunk)?;
+    }
+    Ok(bytes)
+}
+
+/// Every valid value at an address aligned to `align` within `bytes`, a copy of memory starting at `start`.
+fn snapshot_values<T: Scannable>(
+    start: u32,
+    align: u32,
+    bytes: &[u8],
+) -> impl Iterator<Item = (u32, T)> + '_ {
+    aligned_values(start, align, bytes).filter_map(|(addr, value)| Some((addr, value?)))
+}
+
+/// Like [`snapshot_values`], but including addresses without a valid value.
+fn aligned_values<T: Scannable>(
+    start: u32,
+    align: u32,
+    bytes: &[u8],
+) -> impl Iterator<Item = (u32, Option<T>)> + '_ {
+    let size = std::mem::size_of::<T>();
+    let first = (start + align - 1) / align * align;
+    let end = start + bytes.len() as u32;
+    (first..end).step_by(align as usize).map_while(move |addr| {
+        let offset = (addr - start) as usize;
+        Some((addr, decode(bytes.get(offset..offset + size)?)))
+    })
+}
+
+/// Split `results` into runs whose values all lie within [`CHUNK_SIZE`] bytes of the start of the run.
+fn group_results<T>(results: &[(u32, T)], size: u32) -> impl Iterator<Item = &[(u32, T)]> {
+    let mut rest = results;
+    std::iter::from_fn(move || {
+        let (&(start, _), _) = rest.split_first()?;
+        let len = rest
+            .iter()
+            .take_while(|(addr, _)| addr + size - start <= CHUNK_SIZE)
+            .count()
+            .max(1);
+        let (group, tail) = rest.split_at(len);
+        rest = tail;
+        Some(group)
+    })
+}
+
+/// Decode a `T` from bytes in the game's byte order, or `None` if they aren't a valid `T`.
+fn decode<T: CheckedBitPattern + EndianAware>(bytes: &[u8]) -> Option<T> {
+    let mut bytes = bytes.to_vec();
+    T::swap_endian(&mut bytes);
+    bytemuck::checked::try_pod_read_unaligned(&bytes).ok()
+}
@@ -0,0 +1,180 @@
+//! Reading strings from the game's memory.
+//!
+//! Strings don't have a fixed size, so they can't be read with the [`InterfaceBackend::Var`] type constructor.
+//! Instead, [`StringVar`] reads a string in fixed-size chunks until it finds a NUL terminator or reaches its
+//! maximum length, which works with any backend.
+//!
+//! # Examples
+//! ```
+//! use bfbb::game_interface::game_var::{GameVar, InterfaceBackend};
+//! use bfbb::game_interface::string::Encoding;
+//! use bfbb::game_interface::{GameInterface, InterfaceResult};
+//!
+//! /// Read a NUL-terminated string pointed to by the pointer at `ptr_addr`.
+//! fn read_name<F: InterfaceBackend>(
+//!     interface: &GameInterface<F>,
+//!     ptr_addr: usize,
+//! ) -> InterfaceResult<String> {
+//!     interface.string_var([ptr_addr, 0], 64, Encoding::Ascii).get()
+//! }
+//! ```
+//!
+//! ```
+//! use bfbb::game_interface::game_var::GameVar;
+//! use bfbb::game_interface::mock::MockInterface;
+//! use bfbb::game_interface::string::Encoding;
+//! use bfbb::game_interface::InterfaceResult;
+//!
+//! # fn main() -> InterfaceResult<()> {
+//! let mut interface = MockInterface::default();
+//! // A string ending at the very end of the game's memory
+//! interface.write_bytes(0x817F_FFFA, b"HELLO\0")?;
+//! assert_eq!(interface.string_var([0x817F_FFFA], 64, Encoding::Ascii).get()?, "HELLO");
+//! assert_eq!(interface.string_var([0x817F_FFFA], 3, Encoding::Ascii).get()?, "HEL");
+//!
+//! // An unterminated string can't be read past the end of memory
+//! interface.write_bytes(0x817F_FFFA, b"HELLO!")?;
+//! assert!(interface.string_var([0x817F_FFFA], 64, Encoding::Ascii).get().is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{
+    game_var::{GameVar, InterfaceBackend},
+    ptr, InterfaceError, InterfaceResult,
+};
+
+/// The number of bytes read from the game at once.
+const CHUNK_SIZE: usize = 16;
+
+/// The character encoding of a string in the game's memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// 7-bit ASCII, used by asset names and the text of western releases. Bytes outside of the ASCII range are
+    /// decoded as U+FFFD.
+    #[default]
+    Ascii,
+    /// Shift-JIS, used by the text of the Japanese release.
+    ShiftJis,
+}
+
+impl Encoding {
+    /// Decode `bytes` to a [`String`], replacing invalid sequences with U+FFFD.
+    ///
+    /// ```
+    /// use bfbb::game_interface::string::Encoding;
+    ///
+    /// assert_eq!(Encoding::Ascii.decode(b"HB01"), "HB01");
+    /// assert_eq!(Encoding::ShiftJis.decode(&[0x83, 0x58, 0x83, 0x7C]), "スポ");
+    /// ```
+    #[must_use]
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Ascii => bytes
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii() {
+                        b as char
+                    } else {
+                        char::REPLACEMENT_CHARACTER
+                    }
+                })
+                .collect(),
+            Self::ShiftJis => encoding_rs::SHIFT_JIS
+                .decode_without_bom_handling(bytes)
+                .0
+                .into_owned(),
+        }
+    }
+}
+
+/// A [`GameVar`] for a string in the game's memory.
+///
+/// The string ends at the first NUL byte or after `max_len` bytes, whichever comes first.
+pub struct StringVar<F: InterfaceBackend> {
+    path: Vec<usize>,
+    max_len: usize,
+    encoding: Encoding,
+    handle: F::Handle,
+}
+
+impl<F: InterfaceBackend> StringVar<F> {
+    /// Create a new [`StringVar`] for the string at the location given by the pointer-path `path`.
+    ///
+    /// See [`InterfaceBackend::create_var`] for how `path` is followed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `path` is empty.
+    pub fn new(
+        handle: &F::Handle,
+        path: impl Into<Vec<usize>>,
+        max_len: usize,
+        encoding: Encoding,
+    ) -> Self {
+        let path = path.into();
+        assert!(!path.is_empty(), "A string's path must not be empty");
+        Self {
+            path,
+            max_len,
+            encoding,
+            handle: handle.clone(),
+        }
+    }
+
+    /// Read the raw bytes of the string, excluding the NUL terminator.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`InterfaceError`](super::InterfaceError) if the implementation is unable to access the game.
+    pub fn get_bytes(&self) -> InterfaceResult<Vec<u8>> {
+        read_bytes::<F>(&self.handle, &self.path, self.max_len, true)
+    }
+}
+
+/// Read up to `len` bytes at the location given by the non-empty pointer-path `path`, stopping early at the first NUL
+/// byte if `until_nul` is set.
+///
+/// When stopping at a NUL byte, reads never extend past [`MEMORY_END`](ptr::MEMORY_END), so a string near the end of
+/// memory can be read with any `len` as long as it is terminated before then.
+pub(super) fn read_bytes<F: InterfaceBackend>(
+    handle: &F::Handle,
+    path: &[usize],
+    len: usize,
+    until_nul: bool,
+) -> InterfaceResult<Vec<u8>> {
+    let addr = ptr::resolve_path::<F>(handle, path)?;
+    let mut bytes = Vec::new();
+    let mut chunk = [0; CHUNK_SIZE];
+    while bytes.len() < len {
+        let chunk_addr = u32::try_from(bytes.len())
+            .ok()
+            .and_then(|offset| addr.checked_add(offset))
+            .ok_or(InterfaceError::InvalidPointer(addr))?;
+        let mut chunk_len = CHUNK_SIZE.min(len - bytes.len());
+        if until_nul {
+            let remaining = ptr::MEMORY_END.saturating_sub(chunk_addr) as usize;
+            if remaining == 0 {
+                return Err(InterfaceError::InvalidPointer(chunk_addr));
+            }
+            chunk_len = chunk_len.min(remaining);
+        }
+        let chunk = &mut chunk[..chunk_len];
+        F::read_bytes(handle, chunk_addr, chunk)?;
+        if let Some(nul) = chunk.iter().position(|&b| b == 0).filter(|_| until_nul) {
+            bytes.extend_from_slice(&chunk[..nul]);
+            break;
+        }
+        bytes.extend_from_slice(chunk);
+    }
+    Ok(bytes)
+}
+
+impl<F: InterfaceBackend> GameVar for StringVar<F> {
+    type Target = String;
+
+    fn get(&self) -> InterfaceResult<String> {
+        Ok(self.encoding.decode(&self.get_bytes()?))
+    }
+}
@@ -0,0 +1,398 @@
+//! Parsing of the game's HIP/HOP level archives.
+//!
+//! Each [`Level`] is stored on the disc as a pair of archives, `<scene>.HIP` and `<scene>.HOP` (e.g. `hb/hb01.HIP`),
+//! which contain every asset used by that level. Both use the same `HIPA` format, a tree of tagged sections:
+//!
+//! ```text
+//! HIPA
+//! PACK            Package information
+//!   PVER PFLG PCNT PCRT PMOD PLAT
+//! DICT            Asset dictionary
+//!   ATOC            Asset table
+//!     AINF
+//!     AHDR          One for each asset (id, type, offset, size, ...)
+//!       ADBG          Asset name and file name
+//!   LTOC            Layer table
+//!     LINF
+//!     LHDR          One for each layer (type and asset ids)
+//!       LDBG
+//! STRM            Asset data
+//!   DHDR
+//!   DPAK
+//! ```
+//!
+//! Each section is a 4-byte tag followed by a big-endian `u32` length and its contents.
+//!
+//! # Examples
+//! ```no_run
+//! use bfbb::hip::LevelArchives;
+//! use bfbb::Level;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let archives = LevelArchives::open("bfbb/files", Level::JellyfishRock)?;
+//! for asset in archives.hip.assets() {
+//!     println!("{:#010X} {} {}", asset.id, asset.asset_type(), asset.name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::{asset::str_hash, Level, Spatula};
+
+pub mod scene;
+
+/// Result type for reading archives.
+pub type HipResult<T> = std::result::Result<T, Error>;
+
+/// Error type for failures to read an archive.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The archive could not be read from disk.
+    #[error("Archive could not be read")]
+    Io(#[from] std::io::Error),
+    /// The data is not a `HIPA` archive.
+    #[error("Not a HIP/HOP archive")]
+    InvalidMagic,
+    /// A section ended before its contents could be read.
+    #[error("'{}' section ended unexpectedly", String::from_utf8_lossy(.0))]
+    UnexpectedEof([u8; 4]),
+    /// A section required to read the archive is not present.
+    #[error("Missing '{}' section", String::from_utf8_lossy(.0))]
+    MissingSection([u8; 4]),
+    /// An asset's data lies outside of the archive.
+    #[error("Data for asset {0:#010X} is out of bounds")]
+    AssetOutOfBounds(u32),
+    /// The entity at a spatula's offset is not the expected pickup asset.
+    #[error("Entity for {0:?} ({1:#010X}) is not a spatula pickup")]
+    UnexpectedAsset(Spatula, u32),
+}
+
+/// Which of a level's two archives to access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArchiveKind {
+    /// The `.HIP` archive
+    Hip,
+    /// The `.HOP` archive
+    Hop,
+}
+
+/// An entry in an archive's asset table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    /// The id of this asset, the [`str_hash`] of its name.
+    pub id: u32,
+    /// The asset type, a 4-byte tag such as `PKUP` or `MODL`.
+    pub type_tag: [u8; 4],
+    /// The name of this asset.
+    pub name: String,
+    /// The name of the file this asset was created from.
+    pub file_name: String,
+    /// Asset flags (e.g. whether the asset is read-only or contains pointers).
+    pub flags: u32,
+    /// Checksum of this asset's data.
+    pub checksum: u32,
+    offset: usize,
+    size: usize,
+}
+
+/// An entry in an archive's layer table, grouping assets that are loaded together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    /// The type of this layer (e.g. textures, models, default).
+    pub layer_type: u32,
+    /// The ids of the assets in this layer, in load order.
+    pub assets: Vec<u32>,
+}
+
+/// A parsed `HIPA` archive.
+#[derive(Debug, Clone)]
+pub struct Archive {
+    bytes: Vec<u8>,
+    assets: Vec<Asset>,
+    layers: Vec<Layer>,
+}
+
+/// The `.HIP` and `.HOP` archives of a single level.
+#[derive(Debug, Clone)]
+pub struct LevelArchives {
+    /// The level these archives belong to
+    pub level: Level,
+    /// The level's `.HIP` archive
+    pub hip: Archive,
+    /// The level's `.HOP` archive. Not every level has one.
+    pub hop: Option<Archive>,
+}
+
+impl Asset {
+    /// The asset type as a string, such as `"PKUP"`.
+    #[must_use]
+    pub fn asset_type(&self) -> String {
+        String::from_utf8_lossy(&self.type_tag).into_owned()
+    }
+
+    /// The size of this asset's data in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Archive {
+    /// Parse an archive.
+    ///
+    /// ```
+    /// use bfbb::asset::str_hash;
+    /// use bfbb::hip::Archive;
+    ///
+    /// fn section(tag: &[u8; 4], contents: &[u8]) -> Vec<u8> {
+    ///     [&tag[..], &(contents.len() as u32).to_be_bytes(), contents].concat()
+    /// }
+    ///
+    /// let id = str_hash("SPATULA");
+    /// let build = |offset: u32| {
+    ///     let debug = section(b"ADBG", &[&[0; 4][..], b"SPATULA\0", b"spatula.bin\0", &[0; 4]].concat());
+    ///     let header = [
+    ///         &id.to_be_bytes()[..],
+    ///         b"PKUP",
+    ///         &offset.to_be_bytes(),
+    ///         &4u32.to_be_bytes(), // size
+    ///         &[0; 8],
+    ///         &debug,
+    ///     ]
+    ///     .concat();
+    ///     let layer = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &id.to_be_bytes()].concat();
+    ///     let dict = [
+    ///         section(b"ATOC", &[section(b"AINF", &[0; 4]), section(b"AHDR", &header)].concat()),
+    ///         section(b"LTOC", &[section(b"LINF", &[0; 4]), section(b"LHDR", &layer)].concat()),
+    ///     ]
+    ///     .concat();
+    ///     let stream = [section(b"DHDR", &[0xFF; 4]), section(b"DPAK", &[0, 0, 0, 0, 1, 2, 3, 4])].concat();
+    ///     [section(b"HIPA", &[]), section(b"DICT", &dict), section(b"STRM", &stream)].concat()
+    /// };
+    /// // The asset's data is at the very end of the archive
+    /// let bytes = build(build(0).len() as u32 - 4);
+    ///
+    /// let archive = Archive::from_bytes(bytes).unwrap();
+    /// let asset = archive.asset_by_name("spatula").unwrap();
+    /// assert_eq!(asset.asset_type(), "PKUP");
+    /// assert_eq!(asset.file_name, "spatula.bin");
+    /// assert_eq!(archive.data(asset), &[1, 2, 3, 4]);
+    /// assert_eq!(archive.layers().next().unwrap().assets, vec![id]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if `bytes` is not a valid `HIPA` archive.
+    pub fn from_bytes(bytes: Vec<u8>) -> HipResult<Self> {
+        let sections = sections(b"HIPA", &bytes)?;
+        if sections.first().map(|(tag, _)| tag) != Some(b"HIPA") {
+            return Err(Error::InvalidMagic);
+        }
+
+        let dict = find(&sections, b"DICT")?;
+        let dict = self::sections(b"DICT", dict)?;
+        let asset_table = self::sections(b"ATOC", find(&dict, b"ATOC")?)?;
+        let layer_table = self::sections(b"LTOC", find(&dict, b"LTOC")?)?;
+
+        let assets = asset_table
+            .iter()
+            .filter(|(tag, _)| tag == b"AHDR")
+            .map(|(_, data)| read_asset(data))
+            .collect::<HipResult<Vec<_>>>()?;
+        let layers = layer_table
+            .iter()
+            .filter(|(tag, _)| tag == b"LHDR")
+            .map(|(_, data)| read_layer(data))
+            .collect::<HipResult<_>>()?;
+
+        if let Some(asset) = assets.iter().find(|a| a.offset + a.size > bytes.len()) {
+            return Err(Error::AssetOutOfBounds(asset.id));
+        }
+
+        Ok(Self {
+            bytes,
+            assets,
+            layers,
+        })
+    }
+
+    /// Read and parse an archive from disk.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if the file can't be read or is not a valid `HIPA` archive.
+    pub fn open(path: impl AsRef<Path>) -> HipResult<Self> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Every asset in this archive, in the order of the asset table.
+    pub fn assets(&self) -> impl Iterator<Item = &Asset> {
+        self.assets.iter()
+    }
+
+    /// Every layer in this archive, in load order.
+    pub fn layers(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter()
+    }
+
+    /// Find an asset by its id.
+    #[must_use]
+    pub fn asset(&self, id: u32) -> Option<&Asset> {
+        self.assets.iter().find(|a| a.id == id)
+    }
+
+    /// Find an asset by its name.
+    #[must_use]
+    pub fn asset_by_name(&self, name: &str) -> Option<&Asset> {
+        self.asset(str_hash(name))
+    }
+
+    /// Every asset of the given type, such as `b"PKUP"`.
+    pub fn assets_of_type<'a>(&'a self, type_tag: &'a [u8; 4]) -> impl Iterator<Item = &'a Asset> {
+        self.assets.iter().filter(move |a| &a.type_tag == type_tag)
+    }
+
+    /// The raw data of an asset from this archive.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `asset` did not come from this archive.
+    #[must_use]
+    pub fn data(&self, asset: &Asset) -> &[u8] {
+        &self.bytes[asset.offset..asset.offset + asset.size]
+    }
+}
+
+impl LevelArchives {
+    /// Read both archives of `level` from an extracted copy of the disc's filesystem, where `root` is the
+    /// directory containing the level folders (`hb`, `jf`, ...).
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if the `.HIP` archive is missing or if either archive is invalid.
+    pub fn open(root: impl AsRef<Path>, level: Level) -> HipResult<Self> {
+        let root = root.as_ref();
+        let hip = Archive::open(archive_path(root, level, ArchiveKind::Hip))?;
+        let hop = match Archive::open(archive_path(root, level, ArchiveKind::Hop)) {
+            Ok(hop) => Some(hop),
+            Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        Ok(Self { level, hip, hop })
+    }
+
+    /// Every archive for this level, in the order that the game loads them.
+    pub fn archives(&self) -> impl Iterator<Item = &Archive> {
+        std::iter::once(&self.hip).chain(self.hop.as_ref())
+    }
+
+    /// Find an asset by its id in either archive.
+    #[must_use]
+    pub fn asset(&self, id: u32) -> Option<(&Archive, &Asset)> {
+        self.archives()
+            .find_map(|a| a.asset(id).map(|asset| (a, asset)))
+    }
+}
+
+/// The location of one of `level`'s archives, relative to `root`.
+///
+/// ```
+/// use std::path::Path;
+///
+/// use bfbb::hip::{archive_path, ArchiveKind};
+/// use bfbb::Level;
+///
+/// let path = archive_path("files", Level::BikiniBottom, ArchiveKind::Hip);
+/// assert_eq!(path, Path::new("files/hb/hb01.HIP"));
+/// ```
+pub fn archive_path(root: impl AsRef<Path>, level: Level, kind: ArchiveKind) -> PathBuf {
+    let scene_id: [u8; 4] = level.into();
+    let scene = String::from_utf8_lossy(&scene_id).to_lowercase();
+    let extension = match kind {
+        ArchiveKind::Hip => "HIP",
+        ArchiveKind::Hop => "HOP",
+    };
+    root.as_ref()
+        .join(&scene[..2])
+        .join(format!("{scene}.{extension}"))
+}
+
+/// Split `data` into a list of sections.
+fn sections<'a>(parent: &[u8; 4], mut data: &'a [u8]) -> HipResult<Vec<([u8; 4], &'a [u8])>> {
+    let mut sections = Vec::new();
+    while !data.is_empty() {
+        let header = data.get(..8).ok_or(Error::UnexpectedEof(*parent))?;
+        let tag: [u8; 4] = header[..4].try_into().unwrap();
+        let len = read_u32(header, 4) as usize;
+        let contents = data.get(8..8 + len).ok_or(Error::UnexpectedEof(tag))?;
+        sections.push((tag, contents));
+        data = &data[8 + len..];
+    }
+    Ok(sections)
+}
+
+fn find<'a>(sections: &[([u8; 4], &'a [u8])], tag: &[u8; 4]) -> HipResult<&'a [u8]> {
+    sections
+        .iter()
+        .find(|(t, _)| t == tag)
+        .map(|(_, data)| *data)
+        .ok_or(Error::MissingSection(*tag))
+}
+
+fn read_asset(data: &[u8]) -> HipResult<Asset> {
+    const HEADER_SIZE: usize = 0x18;
+    let header = data
+        .get(..HEADER_SIZE)
+        .ok_or(Error::UnexpectedEof(*b"AHDR"))?;
+    let debug = sections(b"AHDR", &data[HEADER_SIZE..])?;
+    let debug = find(&debug, b"ADBG")?;
+
+    // ADBG: alignment, name, file name, checksum
+    let mut strings = debug.get(4..).ok_or(Error::UnexpectedEof(*b"ADBG"))?;
+    let name = read_string(&mut strings);
+    let file_name = read_string(&mut strings);
+    let checksum = strings
+        .get(..4)
+        .map_or(0, |c| u32::from_be_bytes(c.try_into().unwrap()));
+
+    Ok(Asset {
+        id: read_u32(header, 0),
+        type_tag: header[4..8].try_into().unwrap(),
+        offset: read_u32(header, 8) as usize,
+        size: read_u32(header, 0xC) as usize,
+        flags: read_u32(header, 0x14),
+        name,
+        file_name,
+        checksum,
+    })
+}
+
+fn read_layer(data: &[u8]) -> HipResult<Layer> {
+    let header = data.get(..8).ok_or(Error::UnexpectedEof(*b"LHDR"))?;
+    let count = read_u32(header, 4) as usize;
+    let ids = data
+        .get(8..8 + count * 4)
+        .ok_or(Error::UnexpectedEof(*b"LHDR"))?;
+    Ok(Layer {
+        layer_type: read_u32(header, 0),
+        assets: ids.chunks_exact(4).map(|id| read_u32(id, 0)).collect(),
+    })
+}
+
+/// Read a null-terminated string, padded to an even length, advancing `data` past it.
+fn read_string(data: &mut &[u8]) -> String {
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    let string = String::from_utf8_lossy(&data[..len]).into_owned();
+    let padded = (len + 2) & !1;
+    *data = data.get(padded..).unwrap_or_default();
+    string
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
@@ -0,0 +1,193 @@
+//! Reconstructing the layout of a scene from its archives.
+//!
+//! When a scene is loaded, the game creates an entity for every asset of an "object" type and stores them in a
+//! single array (see `game_interface::entity::Entities`). The array is filled one asset type at a
+//! time, in the order of the game's scene initialization table (`sInitTable` in `zScene.cpp`), and within each type
+//! in the order that the assets appear in the level's archives.
+//!
+//! This allows calculating the index of any entity, such as those used by [`Spatula::get_offset`], from the
+//! archives of any build of the game.
+//!
+//! **NOTE:** This module is experimental. [`SCENE_INIT_ORDER`] and the order of archives used by [`entity_order`]
+//! have not yet been checked against the GameCube release's archives, so generated tables may not match
+//! [`Spatula::get_offset`]. The second example below is that check.
+//!
+//! # Examples
+//! ```no_run
+//! use bfbb::hip::scene::SpatulaTable;
+//! use bfbb::hip::LevelArchives;
+//! use bfbb::Level;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let levels = [Level::BikiniBottom, Level::JellyfishRock, Level::DowntownStreets];
+//! let open_all = |root: &str| {
+//!     levels
+//!         .into_iter()
+//!         .map(|level| LevelArchives::open(root, level))
+//!         .collect::<Result<Vec<_>, _>>()
+//! };
+//!
+//! // Identify each spatula's asset using the built-in table for the GameCube release
+//! let ids = SpatulaTable::reference_ids(&open_all("gcn/files")?)?;
+//! // Then locate those assets in the PAL release
+//! let table = SpatulaTable::generate(&ids, &open_all("pal/files")?);
+//! println!("{}", table.to_rust());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The built-in table can be checked against the GameCube release's archives. This needs the `.HIP` (and `.HOP`,
+//! where present) of every level with a spatula, extracted from the disc to `gcn/files`.
+//! [`SpatulaTable::reference_ids`] fails unless every offset lands on a pickup when entities are ordered by
+//! [`SCENE_INIT_ORDER`], and no two spatulas may share a pickup:
+//! ```no_run
+//! use std::collections::HashSet;
+//!
+//! use bfbb::hip::scene::SpatulaTable;
+//! use bfbb::hip::LevelArchives;
+//! use bfbb::{IntoEnumIterator, Spatula};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut levels = Vec::new();
+//! for level in Spatula::iter().map(|s| s.get_level()) {
+//!     if !levels.contains(&level) {
+//!         levels.push(level);
+//!     }
+//! }
+//! let levels = levels
+//!     .into_iter()
+//!     .map(|level| LevelArchives::open("gcn/files", level))
+//!     .collect::<Result<Vec<_>, _>>()?;
+//!
+//! let ids = SpatulaTable::reference_ids(&levels)?;
+//! assert_eq!(ids.values().collect::<HashSet<_>>().len(), ids.len());
+//!
+//! let table = SpatulaTable::generate(&ids, &levels);
+//! for spatula in Spatula::iter() {
+//!     assert_eq!(table.get(spatula), spatula.get_offset(), "{spatula:?}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use strum::IntoEnumIterator;
+
+use crate::Spatula;
+
+use super::{Error, HipResult, LevelArchives};
+
+/// The asset types that create entities, in the order they are added to a scene's entity array.
+///
+/// This is the order of the `sInitTable` array in `zScene.cpp` of the BfBB decompilation
+/// (<https://github.com/bfbbdecomp/bfbb>), skipping entries that don't create entities.
+pub const SCENE_INIT_ORDER: &[[u8; 4]] = &[
+    *b"TRIG", *b"MVPT", *b"PEND", *b"HANG", *b"SIMP", *b"BUTN", *b"PLAT", *b"PLYR", *b"PKUP",
+    *b"VIL ", *b"PORT", *b"CAM ", *b"TIMR", *b"CNTR", *b"SFX ", *b"GRUP", *b"SURF", *b"DPAT",
+    *b"COND", *b"ENV ", *b"FOG ", *b"UI  ", *b"UIFT", *b"PARE", *b"PARS", *b"EGEN", *b"SCRP",
+    *b"CSNM", *b"DSCO", *b"ALST", *b"SDFX", *b"DSTR", *b"DYNA",
+];
+
+/// The asset type of the pickups used for golden spatulas.
+pub const SPATULA_ASSET_TYPE: &[u8; 4] = b"PKUP";
+
+/// The asset ids of every entity in a level's scene, in the order of the scene's entity array.
+#[must_use]
+pub fn entity_order(archives: &LevelArchives) -> Vec<u32> {
+    SCENE_INIT_ORDER
+        .iter()
+        .flat_map(|asset_type| {
+            archives
+                .archives()
+                .flat_map(move |a| a.assets_of_type(asset_type))
+                .map(|a| a.id)
+        })
+        .collect()
+}
+
+/// A table of the index of each [`Spatula`] within its level's entity array, intended to be equivalent to
+/// [`Spatula::get_offset`] for a specific build of the game.
+///
+/// **NOTE:** This is experimental, see the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpatulaTable {
+    offsets: HashMap<Spatula, usize>,
+}
+
+impl SpatulaTable {
+    /// Identify the asset id of each spatula using [`Spatula::get_offset`], which is only valid for the
+    /// GameCube release. `levels` must therefore come from the GameCube release.
+    ///
+    /// Spatulas without an offset or whose level is not in `levels` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::UnexpectedAsset`] if the entity at a spatula's offset is not a pickup, which means
+    /// that `levels` is not from the GameCube release.
+    pub fn reference_ids(levels: &[LevelArchives]) -> HipResult<HashMap<Spatula, u32>> {
+        let mut ids = HashMap::new();
+        for archives in levels {
+            let order = entity_order(archives);
+            for spatula in Spatula::iter().filter(|s| s.get_level() == archives.level) {
+                let Some(id) = spatula.get_offset().and_then(|i| order.get(i)) else {
+                    continue;
+                };
+                match archives.asset(*id) {
+                    Some((_, asset)) if &asset.type_tag == SPATULA_ASSET_TYPE => {
+                        ids.insert(spatula, *id);
+                    }
+                    _ => return Err(Error::UnexpectedAsset(spatula, *id)),
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Generate the table for the build that `levels` come from, given the asset id of each spatula
+    /// (see [`reference_ids`](Self::reference_ids)).
+    ///
+    /// Spatulas whose asset can't be found are left out of the table.
+    ///
+    /// **NOTE:** This is experimental and unverified, so the result should be checked in game before being used in
+    /// place of [`Spatula::get_offset`]. See the [module documentation](self).
+    #[must_use]
+    pub fn generate(ids: &HashMap<Spatula, u32>, levels: &[LevelArchives]) -> Self {
+        let mut offsets = HashMap::new();
+        for archives in levels {
+            let order = entity_order(archives);
+            for spatula in Spatula::iter().filter(|s| s.get_level() == archives.level) {
+                let index = ids
+                    .get(&spatula)
+                    .and_then(|id| order.iter().position(|x| x == id));
+                if let Some(index) = index {
+                    offsets.insert(spatula, index);
+                }
+            }
+        }
+        Self { offsets }
+    }
+
+    /// The index of `spatula` within its level's entity array.
+    #[must_use]
+    pub fn get(&self, spatula: Spatula) -> Option<usize> {
+        self.offsets.get(&spatula).copied()
+    }
+
+    /// Emit this table as the body of a Rust `match` in the same form as [`Spatula::get_offset`].
+    #[must_use]
+    pub fn to_rust(&self) -> String {
+        let mut out = String::from("match *self {\n");
+        for spatula in Spatula::iter() {
+            let offset = match self.get(spatula) {
+                Some(i) => format!("Some({i:#x})"),
+                None => "None".to_owned(),
+            };
+            // Writing to a String can't fail
+            let _ = writeln!(out, "    Self::{spatula:?} => {offset},");
+        }
+        out.push('}');
+        out
+    }
+}
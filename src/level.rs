@@ -1,3 +1,14 @@
+use std::str::FromStr;
+
+use strum::IntoEnumIterator;
+use strum_macros::{EnumCount, EnumIter};
+
+use crate::{
+    parse::{parse, ParseError},
+    scene::{NotALevelError, SceneId},
+    World,
+};
+
 /// Convenient definition for every level in the game.
 ///
 /// # Scene IDs
@@ -30,7 +41,7 @@
 /// let level = Level::SpongebobHouse;
 /// println!("{level}"); // Prints "Spongebob's House"
 /// assert_eq!(level.to_string().as_str(), "Spongebob's House");
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(EnumIter, EnumCount, Debug, PartialEq, Eq, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum Level {
     #[doc(hidden)]
@@ -154,6 +165,21 @@ pub enum Level {
     SpongeballArena,
 }
 
+impl Level {
+    /// Returns the world this level belongs to, or `None` for levels outside of any world (such as the main menu).
+    ///
+    /// ```
+    /// use bfbb::{Level, World};
+    ///
+    /// assert_eq!(Level::KrustyKrab.world(), Some(World::BikiniBottom));
+    /// assert_eq!(Level::MainMenu.world(), None);
+    /// ```
+    #[must_use]
+    pub fn world(&self) -> Option<World> {
+        World::iter().find(|w| w.levels().contains(self))
+    }
+}
+
 impl std::fmt::Display for Level {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match *self {
@@ -214,11 +240,50 @@ impl std::fmt::Display for Level {
     }
 }
 
+impl FromStr for Level {
+    type Err = ParseError<Level>;
+
+    /// Parse a level from its in-game name, its identifier or its scene id, ignoring case, whitespace and
+    /// punctuation.
+    ///
+    /// Small typos are tolerated as long as only one level is a close match.
+    ///
+    /// ```
+    /// use bfbb::Level;
+    ///
+    /// assert_eq!("spongebob's house".parse(), Ok(Level::SpongebobHouse));
+    /// assert_eq!("GraveyardShip".parse(), Ok(Level::GraveyardShip));
+    /// assert_eq!("hb01".parse(), Ok(Level::BikiniBottom));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(
+            s,
+            Level::iter().map(|level| {
+                let scene_id: [u8; 4] = level.into();
+                let keys = [
+                    level.to_string(),
+                    format!("{level:?}"),
+                    String::from_utf8_lossy(&scene_id).into_owned(),
+                ];
+                (level, keys)
+            }),
+        )
+    }
+}
+
 impl TryFrom<[u8; 4]> for Level {
-    type Error = &'static str;
+    type Error = NotALevelError;
 
     fn try_from(scene_id: [u8; 4]) -> Result<Self, Self::Error> {
-        match &scene_id {
+        SceneId(scene_id).try_into()
+    }
+}
+
+impl TryFrom<SceneId> for Level {
+    type Error = NotALevelError;
+
+    fn try_from(scene_id: SceneId) -> Result<Self, Self::Error> {
+        match &scene_id.0 {
             b"MNU3" => Ok(Level::MainMenu),
             b"HB00" => Ok(Level::IntroCutscene),
             b"HB01" => Ok(Level::BikiniBottom),
@@ -272,7 +337,7 @@ impl TryFrom<[u8; 4]> for Level {
             b"B302" => Ok(Level::ChumBucketLab),
             b"B303" => Ok(Level::ChumBucketBrain),
             b"PG12" => Ok(Level::SpongeballArena),
-            _ => Err("Byte array did not correspond to a level."),
+            _ => Err(NotALevelError(scene_id)),
         }
     }
 }
@@ -8,20 +8,38 @@
 //!
 //! `serde`: Implements [`serde`](https://docs.rs/serde)'s `Serialize`/`Deserialize` for the core data types.
 //!
-//! `game-interface`: Enables the [`game_interface`] module.
+//! `game-interface`: Enables the [`game_interface`] and [`endian`] modules.
+//!
+//! `dmw`: Enables the `game_interface::dmw` module for reading and writing Dolphin Memory Engine watch lists. Implies
+//! `game-interface` and `serde`.
+//!
+//! `registry`: Enables the `game_interface::registry` module for defining variables in TOML or JSON files. Implies
+//! `game-interface` and `serde`.
 //!
 #![warn(missing_docs)]
 #![warn(rust_2018_idioms)]
 
 pub use level::Level;
-pub use spatula::Spatula;
+pub use parse::ParseError;
+pub use scene::{NotALevelError, SceneId};
+pub use spatula::{Spatula, TaskStatus};
+pub use world::World;
 
 pub use strum::{EnumCount, IntoEnumIterator};
 
+pub mod asset;
+pub mod cheat;
 #[cfg(feature = "game-interface")]
 pub mod game_interface;
 
-mod endian;
+#[cfg(feature = "game-interface")]
+pub mod endian;
 pub mod game_state;
+pub mod hip;
 mod level;
+mod parse;
+pub mod save;
+mod scene;
 mod spatula;
+pub mod symbols;
+mod world;
@@ -0,0 +1,154 @@
+use std::fmt::{Debug, Display};
+
+/// Error type for failing to parse a [`Spatula`](crate::Spatula) or [`Level`](crate::Level) from a string.
+///
+/// Contains the closest candidates to the input, which can be shown to a user as suggestions.
+///
+/// ```
+/// use bfbb::Spatula;
+///
+/// let err = "top of the entrance area".parse::<Spatula>().unwrap_err();
+/// assert_eq!(
+///     err.candidates(),
+///     &[Spatula::TopOfTheEntranceAreaML, Spatula::TopOfTheEntranceAreaFDG]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError<T> {
+    input: String,
+    candidates: Vec<T>,
+}
+
+impl<T> ParseError<T> {
+    /// The string that failed to parse.
+    #[must_use]
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// The closest matches to the input, best match first. May be empty if nothing was close.
+    #[must_use]
+    pub fn candidates(&self) -> &[T] {
+        &self.candidates
+    }
+}
+
+impl<T: Display> Display for ParseError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' did not match any name", self.input)?;
+        if let Some((first, rest)) = self.candidates.split_first() {
+            write!(f, ", did you mean '{first}'")?;
+            for c in rest {
+                write!(f, " or '{c}'")?;
+            }
+            write!(f, "?")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Debug + Display> std::error::Error for ParseError<T> {}
+
+/// The most candidates to suggest when parsing fails.
+const MAX_CANDIDATES: usize = 3;
+
+/// Find the value whose keys best match `input`.
+///
+/// Matching ignores case, whitespace and punctuation. An exact match is preferred, followed by a single key
+/// containing the input and finally a single key within a small edit distance of the input.
+pub(crate) fn parse<T, I, K>(input: &str, values: I) -> Result<T, ParseError<T>>
+where
+    T: Copy + PartialEq,
+    I: IntoIterator<Item = (T, K)>,
+    K: IntoIterator,
+    K::Item: AsRef<str>,
+{
+    let needle = normalize(input);
+    let error = |candidates: Vec<T>| ParseError {
+        input: input.to_owned(),
+        candidates,
+    };
+    if needle.is_empty() {
+        return Err(error(Vec::new()));
+    }
+
+    // Best (lowest) edit distance for each value, and whether any of its keys contain the input
+    let mut scored: Vec<(T, usize, bool)> = values
+        .into_iter()
+        .map(|(value, keys)| {
+            keys.into_iter().map(|k| normalize(k.as_ref())).fold(
+                (value, usize::MAX, false),
+                |(v, dist, contains), key| {
+                    (
+                        v,
+                        dist.min(levenshtein(&needle, &key)),
+                        contains || key.contains(&needle),
+                    )
+                },
+            )
+        })
+        .collect();
+    scored.sort_by_key(|&(_, dist, _)| dist);
+
+    let exact: Vec<T> = scored
+        .iter()
+        .filter(|(_, dist, _)| *dist == 0)
+        .map(|(v, ..)| *v)
+        .collect();
+    match exact.len() {
+        1 => return Ok(exact[0]),
+        2.. => return Err(error(exact)),
+        _ => {}
+    }
+
+    let containing: Vec<T> = scored
+        .iter()
+        .filter(|(.., contains)| *contains)
+        .map(|(v, ..)| *v)
+        .collect();
+    match containing.len() {
+        1 => return Ok(containing[0]),
+        2.. => return Err(error(containing.into_iter().take(MAX_CANDIDATES).collect())),
+        _ => {}
+    }
+
+    let threshold = (needle.len() / 4).max(1);
+    match scored.as_slice() {
+        [(v, dist, _), rest @ ..]
+            if *dist <= threshold && rest.first().map_or(true, |(_, d, _)| d > dist) =>
+        {
+            Ok(*v)
+        }
+        _ => Err(error(
+            scored
+                .iter()
+                .filter(|(_, dist, _)| *dist <= needle.len() / 2)
+                .take(MAX_CANDIDATES)
+                .map(|(v, ..)| *v)
+                .collect(),
+        )),
+    }
+}
+
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            let next = (row[j + 1] + 1).min(row[j] + 1).min(prev + cost);
+            prev = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,387 @@
+//! Raw GameCube memory-card images (`.raw`).
+//!
+//! A memory-card is an array of [`BLOCK_SIZE`] blocks, the first five of which are reserved for the filesystem:
+//!
+//! | Block | Description                       |
+//! |-------|-----------------------------------|
+//! | `0`   | Card header                       |
+//! | `1`   | Directory                         |
+//! | `2`   | Directory (backup)                |
+//! | `3`   | Block allocation table            |
+//! | `4`   | Block allocation table (backup)   |
+//!
+//! Both the directory and the block allocation table are stored twice, and the copy with a valid checksum and the
+//! highest update counter is the active one. [`MemoryCard`] always writes both copies.
+
+use super::gci::{Gci, GciHeader, BLOCK_SIZE, HEADER_SIZE};
+use super::{Error, SaveResult};
+
+/// Number of blocks reserved for the filesystem at the start of every card.
+pub const RESERVED_BLOCKS: usize = 5;
+
+/// Maximum number of files on a single card.
+pub const DIRECTORY_SIZE: usize = 127;
+
+const BLOCKS_PER_MBIT: usize = 0x10;
+
+/// The most blocks a card can have, limited by the number of entries in the block allocation table.
+const MAX_BLOCKS: usize = RESERVED_BLOCKS + (BLOCK_SIZE - BAT_MAP_OFFSET) / 2;
+
+const HEADER_SIZE_OFFSET: usize = 0x22;
+const HEADER_CHECKSUM_OFFSET: usize = 0x1FC;
+
+const DIRECTORY_BLOCK: usize = 1;
+const BAT_BLOCK: usize = 3;
+
+const DIR_COUNTER_OFFSET: usize = 0x1FFA;
+const DIR_CHECKSUM_OFFSET: usize = 0x1FFC;
+
+const BAT_COUNTER_OFFSET: usize = 0x4;
+const BAT_FREE_OFFSET: usize = 0x6;
+const BAT_LAST_ALLOCATED_OFFSET: usize = 0x8;
+const BAT_MAP_OFFSET: usize = 0xA;
+
+const BAT_FREE: u16 = 0x0000;
+const BAT_LAST: u16 = 0xFFFF;
+
+/// A raw GameCube memory-card image, allowing files to be listed, extracted, inserted and deleted.
+///
+/// # Examples
+/// ```no_run
+/// use bfbb::save::card::MemoryCard;
+/// use bfbb::save::SaveGame;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let card = MemoryCard::from_bytes(&std::fs::read("MemoryCardA.USA.raw")?)?;
+/// for header in card.saves() {
+///     let save = SaveGame::from_gci(&card.extract(header)?)?;
+///     println!("{}: {} spatulas", header.file_name(), save.spatula_count());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryCard {
+    image: Vec<u8>,
+    directory: Vec<Option<GciHeader>>,
+    directory_counter: u16,
+    bat: Vec<u16>,
+    bat_counter: u16,
+    last_allocated: u16,
+}
+
+impl MemoryCard {
+    /// Create a newly formatted card with the given size in megabits (e.g. `4` for a "59 block" card).
+    ///
+    /// ```
+    /// use bfbb::save::card::MemoryCard;
+    ///
+    /// let card = MemoryCard::new(4).unwrap();
+    /// assert_eq!(card.free_blocks(), 59);
+    /// assert_eq!(card.files().count(), 0);
+    /// assert!(MemoryCard::new(0).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::InvalidCardSize`] if the card would be too small to hold its filesystem or too large for
+    /// its block allocation table to address.
+    pub fn new(size_mbits: u16) -> SaveResult<Self> {
+        let total_blocks = usize::from(size_mbits) * BLOCKS_PER_MBIT;
+        if total_blocks <= RESERVED_BLOCKS || total_blocks > MAX_BLOCKS {
+            return Err(Error::InvalidCardSize(size_mbits));
+        }
+        let mut image = vec![0; total_blocks * BLOCK_SIZE];
+
+        let header = &mut image[..BLOCK_SIZE];
+        header[HEADER_SIZE_OFFSET..HEADER_SIZE_OFFSET + 2]
+            .copy_from_slice(&size_mbits.to_be_bytes());
+        header[HEADER_SIZE_OFFSET + 4..].fill(0xFF);
+        let sum = checksum(&header[..HEADER_CHECKSUM_OFFSET]);
+        header[HEADER_CHECKSUM_OFFSET..HEADER_CHECKSUM_OFFSET + 4]
+            .copy_from_slice(&sum.to_be_bytes());
+
+        Ok(Self {
+            image,
+            directory: vec![None; DIRECTORY_SIZE],
+            directory_counter: 0,
+            bat: vec![BAT_FREE; total_blocks - RESERVED_BLOCKS],
+            bat_counter: 0,
+            last_allocated: (RESERVED_BLOCKS - 1) as u16,
+        })
+    }
+
+    /// Decode a raw memory-card image.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::InvalidCard`] if `bytes` is not a whole number of blocks, has more blocks than the block
+    /// allocation table can address, or if neither copy of the directory or block allocation table is valid.
+    pub fn from_bytes(bytes: &[u8]) -> SaveResult<Self> {
+        let total_blocks = bytes.len() / BLOCK_SIZE;
+        if bytes.len() % BLOCK_SIZE != 0
+            || total_blocks <= RESERVED_BLOCKS
+            || total_blocks > MAX_BLOCKS
+        {
+            return Err(Error::InvalidCard);
+        }
+        let block = |i: usize| &bytes[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE];
+
+        let directory = [block(DIRECTORY_BLOCK), block(DIRECTORY_BLOCK + 1)]
+            .into_iter()
+            .filter(|b| checksum(&b[..DIR_CHECKSUM_OFFSET]) == read_u32(b, DIR_CHECKSUM_OFFSET))
+            .max_by_key(|b| read_u16(b, DIR_COUNTER_OFFSET))
+            .ok_or(Error::InvalidCard)?;
+        let bat = [block(BAT_BLOCK), block(BAT_BLOCK + 1)]
+            .into_iter()
+            .filter(|b| checksum(&b[BAT_COUNTER_OFFSET..]) == read_u32(b, 0))
+            .max_by_key(|b| read_u16(b, BAT_COUNTER_OFFSET))
+            .ok_or(Error::InvalidCard)?;
+
+        Ok(Self {
+            image: bytes.to_vec(),
+            directory: directory
+                .chunks_exact(HEADER_SIZE)
+                .take(DIRECTORY_SIZE)
+                .map(|entry| match entry[..4] {
+                    [0xFF, 0xFF, 0xFF, 0xFF] => Ok(None),
+                    _ => GciHeader::from_bytes(entry).map(Some),
+                })
+                .collect::<SaveResult<_>>()?,
+            directory_counter: read_u16(directory, DIR_COUNTER_OFFSET),
+            bat: (0..total_blocks - RESERVED_BLOCKS)
+                .map(|i| read_u16(bat, BAT_MAP_OFFSET + i * 2))
+                .collect(),
+            bat_counter: read_u16(bat, BAT_COUNTER_OFFSET),
+            last_allocated: read_u16(bat, BAT_LAST_ALLOCATED_OFFSET),
+        })
+    }
+
+    /// Encode this card as a raw image, updating both copies of the directory and block allocation table.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut image = self.image.clone();
+
+        let mut directory = vec![0xFF; BLOCK_SIZE];
+        for (i, entry) in self.directory.iter().enumerate() {
+            if let Some(header) = entry {
+                directory[i * HEADER_SIZE..(i + 1) * HEADER_SIZE]
+                    .copy_from_slice(&header.to_bytes());
+            }
+        }
+        directory[DIR_COUNTER_OFFSET..DIR_COUNTER_OFFSET + 2]
+            .copy_from_slice(&self.directory_counter.wrapping_add(1).to_be_bytes());
+        let sum = checksum(&directory[..DIR_CHECKSUM_OFFSET]);
+        directory[DIR_CHECKSUM_OFFSET..].copy_from_slice(&sum.to_be_bytes());
+
+        let mut bat = vec![0; BLOCK_SIZE];
+        bat[BAT_COUNTER_OFFSET..BAT_COUNTER_OFFSET + 2]
+            .copy_from_slice(&self.bat_counter.wrapping_add(1).to_be_bytes());
+        bat[BAT_FREE_OFFSET..BAT_FREE_OFFSET + 2]
+            .copy_from_slice(&(self.free_blocks() as u16).to_be_bytes());
+        bat[BAT_LAST_ALLOCATED_OFFSET..BAT_LAST_ALLOCATED_OFFSET + 2]
+            .copy_from_slice(&self.last_allocated.to_be_bytes());
+        for (i, next) in self.bat.iter().enumerate() {
+            let offset = BAT_MAP_OFFSET + i * 2;
+            bat[offset..offset + 2].copy_from_slice(&next.to_be_bytes());
+        }
+        let sum = checksum(&bat[BAT_COUNTER_OFFSET..]);
+        bat[..4].copy_from_slice(&sum.to_be_bytes());
+
+        for i in [DIRECTORY_BLOCK, DIRECTORY_BLOCK + 1] {
+            image[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE].copy_from_slice(&directory);
+        }
+        for i in [BAT_BLOCK, BAT_BLOCK + 1] {
+            image[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE].copy_from_slice(&bat);
+        }
+        image
+    }
+
+    /// Every file on this card.
+    pub fn files(&self) -> impl Iterator<Item = &GciHeader> {
+        self.directory.iter().flatten()
+    }
+
+    /// Every BfBB file on this card.
+    pub fn saves(&self) -> impl Iterator<Item = &GciHeader> {
+        self.files().filter(|h| h.is_bfbb())
+    }
+
+    /// Number of unallocated blocks on this card.
+    #[must_use]
+    pub fn free_blocks(&self) -> usize {
+        self.bat.iter().filter(|&&b| b == BAT_FREE).count()
+    }
+
+    /// Read a file from this card.
+    ///
+    /// Files are matched by their game code, maker code and file name, so `header` may come from either
+    /// [`files`](Self::files) or from a `.gci` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::FileNotFound`] if no such file exists, or [`Error::InvalidCard`] if its blocks are not
+    /// allocated correctly.
+    pub fn extract(&self, header: &GciHeader) -> SaveResult<Gci> {
+        let index = self.find(header).ok_or(Error::FileNotFound)?;
+        let header = self.directory[index].clone().unwrap();
+
+        let mut data = Vec::with_capacity(usize::from(header.block_count) * BLOCK_SIZE);
+        for block in self.chain(header.first_block, header.block_count)? {
+            data.extend_from_slice(&self.image[block * BLOCK_SIZE..(block + 1) * BLOCK_SIZE]);
+        }
+        Ok(Gci { header, data })
+    }
+
+    /// Write a file to this card.
+    ///
+    /// ```
+    /// use bfbb::save::card::MemoryCard;
+    /// use bfbb::save::{Error, Gci, GciHeader};
+    ///
+    /// let mut name = [0; 32];
+    /// name[..4].copy_from_slice(b"save");
+    /// let header = GciHeader {
+    ///     game_code: *b"GQPE",
+    ///     maker_code: *b"78",
+    ///     banner_format: 0,
+    ///     file_name: name,
+    ///     modified_time: 0,
+    ///     image_offset: 0,
+    ///     icon_format: 0,
+    ///     animation_speed: 0,
+    ///     permissions: 4,
+    ///     copy_counter: 0,
+    ///     first_block: 0,
+    ///     block_count: 3,
+    ///     comment_offset: 0,
+    /// };
+    /// let gci = Gci { header, data: vec![0xAB; 3 * 0x2000] };
+    ///
+    /// let mut card = MemoryCard::new(4).unwrap();
+    /// card.insert(&gci).unwrap();
+    ///
+    /// // Round-trip through the raw image
+    /// let mut card = MemoryCard::from_bytes(&card.to_bytes()).unwrap();
+    /// assert_eq!(card.saves().count(), 1);
+    /// assert_eq!(card.free_blocks(), 56);
+    ///
+    /// let deleted = card.delete(&gci.header).unwrap();
+    /// assert_eq!(deleted.data, gci.data);
+    /// assert_eq!(card.free_blocks(), 59);
+    ///
+    /// // The data must fill exactly `block_count` blocks
+    /// let truncated = Gci { header: gci.header.clone(), data: vec![0xAB; 0x2000] };
+    /// assert!(matches!(card.insert(&truncated), Err(Error::DataSizeMismatch { .. })));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if a file with the same name already exists, its data is not the size given by its
+    /// block count, or there is not enough space on the card.
+    pub fn insert(&mut self, gci: &Gci) -> SaveResult<()> {
+        let required = usize::from(gci.header.block_count);
+        if gci.data.len() != required * BLOCK_SIZE {
+            return Err(Error::DataSizeMismatch {
+                expected: required * BLOCK_SIZE,
+                found: gci.data.len(),
+            });
+        }
+        if self.find(&gci.header).is_some() {
+            return Err(Error::FileExists);
+        }
+        let slot = self
+            .directory
+            .iter()
+            .position(Option::is_none)
+            .ok_or(Error::DirectoryFull)?;
+
+        let blocks: Vec<usize> = (0..self.bat.len())
+            .filter(|&i| self.bat[i] == BAT_FREE)
+            .take(required)
+            .map(|i| i + RESERVED_BLOCKS)
+            .collect();
+        if blocks.len() < required {
+            return Err(Error::NotEnoughSpace {
+                required,
+                available: self.free_blocks(),
+            });
+        }
+
+        for (i, &block) in blocks.iter().enumerate() {
+            let next = blocks.get(i + 1).map_or(BAT_LAST, |&b| b as u16);
+            self.bat[block - RESERVED_BLOCKS] = next;
+            self.image[block * BLOCK_SIZE..(block + 1) * BLOCK_SIZE]
+                .copy_from_slice(&gci.data[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE]);
+        }
+        if let Some(&last) = blocks.last() {
+            self.last_allocated = last as u16;
+        }
+
+        let mut header = gci.header.clone();
+        header.first_block = blocks.first().map_or(BAT_LAST, |&b| b as u16);
+        self.directory[slot] = Some(header);
+        Ok(())
+    }
+
+    /// Remove a file from this card, returning its contents.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::FileNotFound`] if no such file exists, or [`Error::InvalidCard`] if its blocks are not
+    /// allocated correctly.
+    pub fn delete(&mut self, header: &GciHeader) -> SaveResult<Gci> {
+        let gci = self.extract(header)?;
+        for block in self.chain(gci.header.first_block, gci.header.block_count)? {
+            self.bat[block - RESERVED_BLOCKS] = BAT_FREE;
+        }
+        let index = self.find(header).unwrap();
+        self.directory[index] = None;
+        Ok(gci)
+    }
+
+    fn find(&self, header: &GciHeader) -> Option<usize> {
+        self.directory.iter().position(|entry| {
+            entry.as_ref().map_or(false, |e| {
+                e.game_code == header.game_code
+                    && e.maker_code == header.maker_code
+                    && e.file_name == header.file_name
+            })
+        })
+    }
+
+    /// Follow a file's blocks through the block allocation table.
+    fn chain(&self, first: u16, count: u16) -> SaveResult<Vec<usize>> {
+        let mut blocks = Vec::with_capacity(count.into());
+        let mut block = first;
+        for _ in 0..count {
+            let index = usize::from(block)
+                .checked_sub(RESERVED_BLOCKS)
+                .filter(|&i| i < self.bat.len())
+                .ok_or(Error::InvalidCard)?;
+            blocks.push(usize::from(block));
+            block = self.bat[index];
+        }
+        Ok(blocks)
+    }
+}
+
+/// The filesystem's checksum, which is the sum of every big-endian `u16` followed by the sum of their inverses.
+fn checksum(bytes: &[u8]) -> u32 {
+    let (sum, inverse) = bytes
+        .chunks_exact(2)
+        .map(|w| u16::from_be_bytes([w[0], w[1]]))
+        .fold((0u16, 0u16), |(sum, inv), w| {
+            (sum.wrapping_add(w), inv.wrapping_add(w ^ 0xFFFF))
+        });
+    let fix = |s: u16| if s == 0xFFFF { 0 } else { s };
+    u32::from(fix(sum)) << 16 | u32::from(fix(inverse))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
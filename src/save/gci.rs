@@ -0,0 +1,192 @@
+//! The `.gci` container used for individual GameCube memory-card files.
+
+use super::{Error, SaveResult};
+
+/// Size of a single block of a GameCube memory-card.
+pub const BLOCK_SIZE: usize = 0x2000;
+
+/// Size of a [`GciHeader`] when encoded.
+pub const HEADER_SIZE: usize = 0x40;
+
+/// The game code prefix shared by every release of BfBB (`GQPE`, `GQPP`, ...).
+pub const GAME_CODE_PREFIX: &[u8; 3] = b"GQP";
+
+/// The maker code for THQ.
+pub const MAKER_CODE: &[u8; 2] = b"78";
+
+/// The header of a `.gci` file.
+///
+/// This is the same structure as an entry in a memory-card's directory, describing the file's owner, name,
+/// graphics and location on the card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct GciHeader {
+    /// Game code of the game that owns this file (e.g. `GQPE`)
+    pub game_code: [u8; 4],
+    /// Maker code of the game that owns this file (e.g. `78`)
+    pub maker_code: [u8; 2],
+    /// Format of the banner image and whether the icon animation ping-pongs.
+    pub banner_format: u8,
+    /// Null-padded name of this file.
+    pub file_name: [u8; 32],
+    /// Time of the last modification, in seconds since 2000-01-01.
+    pub modified_time: u32,
+    /// Offset of the banner/icon graphics within the file data.
+    pub image_offset: u32,
+    /// Format of each of the icon's frames.
+    pub icon_format: u16,
+    /// Animation speed of each of the icon's frames.
+    pub animation_speed: u16,
+    /// Permission flags (public, no-copy, no-move).
+    pub permissions: u8,
+    /// Number of times this file has been copied.
+    pub copy_counter: u8,
+    /// Index of the first block of this file on a memory-card.
+    pub first_block: u16,
+    /// Length of this file's data in blocks.
+    pub block_count: u16,
+    /// Offset of the two 32-byte comment strings within the file data.
+    pub comment_offset: u32,
+}
+
+impl GciHeader {
+    /// Decode a header from the first [`HEADER_SIZE`] bytes of `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::UnexpectedEof`] if `bytes` is too short to contain a header.
+    ///
+    /// # Examples
+    ///
+    /// A header laid out byte-for-byte as a GameCube directory entry:
+    /// ```
+    /// use bfbb::save::gci::{GciHeader, HEADER_SIZE};
+    ///
+    /// # fn main() -> Result<(), bfbb::save::Error> {
+    /// let mut bytes = [0u8; HEADER_SIZE];
+    /// bytes[0x00..0x06].copy_from_slice(b"GQPE78"); // Game and maker code
+    /// bytes[0x06] = 0xFF; // Unused
+    /// bytes[0x07] = 0x02; // Banner format
+    /// bytes[0x08..0x0E].copy_from_slice(b"BfBB 1"); // File name
+    /// bytes[0x28..0x2C].copy_from_slice(&[0x0A, 0x1B, 0x2C, 0x3D]); // Modification time
+    /// bytes[0x2C..0x30].copy_from_slice(&[0x00, 0x00, 0x00, 0x40]); // Image offset
+    /// bytes[0x30..0x32].copy_from_slice(&[0x00, 0x05]); // Icon format
+    /// bytes[0x32..0x34].copy_from_slice(&[0x00, 0x0F]); // Animation speed
+    /// bytes[0x34] = 0x04; // Permissions
+    /// bytes[0x35] = 0x01; // Copy counter
+    /// bytes[0x36..0x38].copy_from_slice(&[0x00, 0x05]); // First block
+    /// bytes[0x38..0x3A].copy_from_slice(&[0x00, 0x03]); // Block count
+    /// bytes[0x3A..0x3C].copy_from_slice(&[0xFF, 0xFF]); // Unused
+    /// bytes[0x3C..0x40].copy_from_slice(&[0x00, 0x00, 0x00, 0x00]); // Comment offset
+    ///
+    /// let header = GciHeader::from_bytes(&bytes)?;
+    /// assert!(header.is_bfbb());
+    /// assert_eq!(header.file_name(), "BfBB 1");
+    /// assert_eq!(header.banner_format, 0x02);
+    /// assert_eq!(header.modified_time, 0x0A1B_2C3D);
+    /// assert_eq!(header.image_offset, 0x40);
+    /// assert_eq!(header.icon_format, 0x05);
+    /// assert_eq!(header.animation_speed, 0x0F);
+    /// assert_eq!(header.permissions, 0x04);
+    /// assert_eq!(header.copy_counter, 0x01);
+    /// assert_eq!(header.first_block, 5);
+    /// assert_eq!(header.block_count, 3);
+    /// assert_eq!(header.comment_offset, 0);
+    ///
+    /// assert_eq!(header.to_bytes(), bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> SaveResult<Self> {
+        let bytes = bytes.get(..HEADER_SIZE).ok_or(Error::UnexpectedEof)?;
+        let u16_at = |i: usize| u16::from_be_bytes([bytes[i], bytes[i + 1]]);
+        let u32_at = |i: usize| u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap());
+        Ok(Self {
+            game_code: bytes[0..4].try_into().unwrap(),
+            maker_code: bytes[4..6].try_into().unwrap(),
+            banner_format: bytes[7],
+            file_name: bytes[8..0x28].try_into().unwrap(),
+            modified_time: u32_at(0x28),
+            image_offset: u32_at(0x2C),
+            icon_format: u16_at(0x30),
+            animation_speed: u16_at(0x32),
+            permissions: bytes[0x34],
+            copy_counter: bytes[0x35],
+            first_block: u16_at(0x36),
+            block_count: u16_at(0x38),
+            comment_offset: u32_at(0x3C),
+        })
+    }
+
+    /// Encode this header.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0xFF; HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&self.game_code);
+        bytes[4..6].copy_from_slice(&self.maker_code);
+        bytes[7] = self.banner_format;
+        bytes[8..0x28].copy_from_slice(&self.file_name);
+        bytes[0x28..0x2C].copy_from_slice(&self.modified_time.to_be_bytes());
+        bytes[0x2C..0x30].copy_from_slice(&self.image_offset.to_be_bytes());
+        bytes[0x30..0x32].copy_from_slice(&self.icon_format.to_be_bytes());
+        bytes[0x32..0x34].copy_from_slice(&self.animation_speed.to_be_bytes());
+        bytes[0x34] = self.permissions;
+        bytes[0x35] = self.copy_counter;
+        bytes[0x36..0x38].copy_from_slice(&self.first_block.to_be_bytes());
+        bytes[0x38..0x3A].copy_from_slice(&self.block_count.to_be_bytes());
+        bytes[0x3C..0x40].copy_from_slice(&self.comment_offset.to_be_bytes());
+        bytes
+    }
+
+    /// The name of this file, with any trailing null bytes removed.
+    #[must_use]
+    pub fn file_name(&self) -> String {
+        let len = self
+            .file_name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.file_name.len());
+        String::from_utf8_lossy(&self.file_name[..len]).into_owned()
+    }
+
+    /// True when this file belongs to any release of BfBB.
+    #[must_use]
+    pub fn is_bfbb(&self) -> bool {
+        self.game_code.starts_with(GAME_CODE_PREFIX) && &self.maker_code == MAKER_CODE
+    }
+}
+
+/// A single memory-card file, as stored in a `.gci` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gci {
+    /// The directory entry for this file.
+    pub header: GciHeader,
+    /// The file's data, always exactly `header.block_count` blocks long.
+    pub data: Vec<u8>,
+}
+
+impl Gci {
+    /// Decode a `.gci` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if `bytes` is too short for the header or the number of blocks it specifies.
+    pub fn from_bytes(bytes: &[u8]) -> SaveResult<Self> {
+        let header = GciHeader::from_bytes(bytes)?;
+        let len = usize::from(header.block_count) * BLOCK_SIZE;
+        let data = bytes
+            .get(HEADER_SIZE..HEADER_SIZE + len)
+            .ok_or(Error::UnexpectedEof)?
+            .to_vec();
+        Ok(Self { header, data })
+    }
+
+    /// Encode this file as a `.gci` file.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.data.len());
+        bytes.extend_from_slice(&self.header.to_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+}
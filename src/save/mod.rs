@@ -0,0 +1,510 @@
+//! Reading and writing BfBB save files without running the game.
+//!
+//! GameCube saves are distributed as `.gci` files, each containing a single memory-card file (see [`Gci`]), or as
+//! raw images of an entire memory-card (see [`MemoryCard`](card::MemoryCard)).
+//! The game's own data lives inside the file's data, following the comment and banner/icon graphics, and is
+//! structured as a tagged block:
+//!
+//! | Offset | Size   | Description                                               |
+//! |--------|--------|-----------------------------------------------------------|
+//! | `0x0`  | `0x4`  | `GDAT` tag                                                |
+//! | `0x4`  | `0x4`  | Length of the chunk data that follows the checksum        |
+//! | `0x8`  | `0x4`  | CRC-32 of the chunk data                                  |
+//! | `0xC`  | `...`  | Chunks, each a 4-byte tag, 4-byte length and its payload  |
+//!
+//! All values are big-endian. [`SaveGame`] interprets the chunks this crate understands and every other chunk is
+//! preserved as-is when writing a save back to a [`Gci`].
+//!
+//! **NOTE:** The checksum and layouts are tested against hand-assembled bytes, but no save file written by the game is
+//! included in this crate's tests yet.
+//!
+//! # Examples
+//! ```no_run
+//! use bfbb::save::{Gci, SaveGame};
+//! use bfbb::{IntoEnumIterator, Spatula, TaskStatus};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut gci = Gci::from_bytes(&std::fs::read("bfbb.gci")?)?;
+//! let mut save = SaveGame::from_gci(&gci)?;
+//! println!("{} spatulas in {:?}", save.spatula_count(), save.play_time);
+//!
+//! // Turn it into a 100% save
+//! for spatula in Spatula::iter() {
+//!     save.tasks.insert(spatula, TaskStatus::Complete);
+//! }
+//! save.write_to_gci(&mut gci)?;
+//! std::fs::write("bfbb-100.gci", gci.to_bytes())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use strum::{EnumCount, IntoEnumIterator};
+use thiserror::Error;
+
+use crate::{Level, SceneId, Spatula, TaskStatus};
+
+pub use self::gci::{Gci, GciHeader};
+
+pub mod card;
+pub mod gci;
+
+const GAME_DATA_TAG: &[u8; 4] = b"GDAT";
+const GAME_DATA_HEADER_SIZE: usize = 0xC;
+
+const LEADER_TAG: &[u8; 4] = b"LEDR";
+const ROOM_TAG: &[u8; 4] = b"ROOM";
+const PLAYER_TAG: &[u8; 4] = b"PLYR";
+const COUNTER_TAG: &[u8; 4] = b"CNTR";
+
+const LEADER_SIZE: usize = 0x4;
+const ROOM_SIZE: usize = 0x4;
+const PLAYER_SIZE: usize = 0x10;
+const COUNTER_SIZE: usize = Spatula::COUNT * 2;
+
+/// Result type for reading or writing saves.
+pub type SaveResult<T> = std::result::Result<T, Error>;
+
+/// Error type for failures to read or write a save.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The data ended before a complete structure could be read.
+    #[error("Save data ended unexpectedly")]
+    UnexpectedEof,
+    /// The file does not contain any BfBB game data.
+    #[error("No game data could be found in this file")]
+    MissingGameData,
+    /// The game data's checksum did not match its contents.
+    #[error("Checksum mismatch (expected {expected:#010X}, found {found:#010X})")]
+    ChecksumMismatch {
+        /// The checksum stored in the file
+        expected: u32,
+        /// The checksum calculated from the file's contents
+        found: u32,
+    },
+    /// A chunk required to build a [`SaveGame`] is not present.
+    #[error("Missing '{}' chunk", String::from_utf8_lossy(.0))]
+    MissingChunk([u8; 4]),
+    /// A chunk is too short to contain the data it should.
+    #[error("'{}' chunk is malformed", String::from_utf8_lossy(.0))]
+    InvalidChunk([u8; 4]),
+    /// The save's current scene is not a [`Level`].
+    #[error("Scene '{0}' is not a level")]
+    InvalidLevel(SceneId),
+    /// A task's counter is not a valid [`TaskStatus`].
+    #[error("{0:?} has invalid status {1}")]
+    InvalidTaskStatus(Spatula, i16),
+    /// A memory-card image is malformed.
+    #[error("Memory card image is corrupt or not a memory card")]
+    InvalidCard,
+    /// A memory-card size is too small to hold the filesystem or too large for it to address.
+    #[error("Memory cards can not be {0} megabits")]
+    InvalidCardSize(u16),
+    /// The requested file does not exist on a memory-card.
+    #[error("File not found on memory card")]
+    FileNotFound,
+    /// A file with the same name already exists on a memory-card.
+    #[error("File already exists on memory card")]
+    FileExists,
+    /// Every directory entry on a memory-card is in use.
+    #[error("Memory card directory is full")]
+    DirectoryFull,
+    /// There are not enough free blocks on a memory-card for a file.
+    #[error("File requires {required} blocks but only {available} are free")]
+    NotEnoughSpace {
+        /// Number of blocks needed by the file
+        required: usize,
+        /// Number of free blocks on the card
+        available: usize,
+    },
+    /// A file's data is not the size given by its block count.
+    #[error("File should be {expected:#X} bytes but is {found:#X}")]
+    DataSizeMismatch {
+        /// Number of bytes given by the file's block count
+        expected: usize,
+        /// Number of bytes of data in the file
+        found: usize,
+    },
+    /// The game data no longer fits within the file's blocks.
+    #[error("Game data requires {required:#X} bytes but only {available:#X} are available")]
+    DataTooLarge {
+        /// Number of bytes needed to store the game data
+        required: usize,
+        /// Number of bytes available in the file
+        available: usize,
+    },
+}
+
+/// Which powers have been unlocked in a save.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Powers {
+    /// Whether the bubble bowl is unlocked
+    pub bubble_bowl: bool,
+    /// Whether the cruise bubble is unlocked
+    pub cruise_bubble: bool,
+}
+
+/// The progress stored in a single BfBB save.
+///
+/// # Examples
+/// ```
+/// use bfbb::save::SaveGame;
+/// use bfbb::{Level, Spatula, TaskStatus};
+///
+/// let mut save = SaveGame::default();
+/// save.level = Level::JellyfishRock;
+/// save.tasks.insert(Spatula::TopOfTheHill, TaskStatus::Complete);
+///
+/// let bytes = save.to_game_data();
+/// let read = SaveGame::from_game_data(&bytes).expect("Save should round-trip");
+/// assert_eq!(read, save);
+/// assert_eq!(read.spatula_count(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SaveGame {
+    /// The pause-menu status of every task.
+    pub tasks: HashMap<Spatula, TaskStatus>,
+    /// Number of Patrick's socks collected.
+    pub socks: u32,
+    /// Number of shiny objects held.
+    pub shiny_objects: u32,
+    /// The level the game will resume in.
+    pub level: Level,
+    /// Which powers have been unlocked.
+    pub powers: Powers,
+    /// Total time played on this save.
+    pub play_time: Duration,
+}
+
+impl Default for SaveGame {
+    fn default() -> Self {
+        Self {
+            tasks: Spatula::iter().map(|s| (s, TaskStatus::Locked)).collect(),
+            socks: 0,
+            shiny_objects: 0,
+            level: Level::BikiniBottom,
+            powers: Powers::default(),
+            play_time: Duration::ZERO,
+        }
+    }
+}
+
+impl SaveGame {
+    /// The number of golden spatulas collected, which is the number of [`TaskStatus::Complete`] tasks.
+    #[must_use]
+    pub fn spatula_count(&self) -> u32 {
+        self.tasks
+            .values()
+            .filter(|&&s| s == TaskStatus::Complete)
+            .count() as u32
+    }
+
+    /// Read a save from a memory-card file.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if `gci` does not contain valid BfBB game data.
+    pub fn from_gci(gci: &Gci) -> SaveResult<Self> {
+        let offset = find_game_data(&gci.data)?;
+        Self::from_game_data(&gci.data[offset..])
+    }
+
+    /// Write this save into a memory-card file, replacing the game data already stored within it.
+    ///
+    /// Only the part of each chunk represented by [`SaveGame`] is overwritten. Any bytes following it within the
+    /// chunk, chunks that are not represented by [`SaveGame`] and the rest of the file are kept as-is, and the checksum
+    /// is recalculated. Missing chunks are appended, which may only overwrite zeroed padding following the game data.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::save::gci::{BLOCK_SIZE, HEADER_SIZE};
+    /// use bfbb::save::{Error, Gci, GciHeader, SaveGame};
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// // Game data whose `PLYR` chunk has four bytes more than this crate understands
+    /// let mut data = b"GDAT\0\0\0\xE4".to_vec();
+    /// data.extend_from_slice(&[0xA4, 0x84, 0x9D, 0x71]); // Checksum
+    /// data.extend_from_slice(b"LEDR\0\0\0\x04\0\0\x0E\x10ROOM\0\0\0\x04HB01");
+    /// data.extend_from_slice(b"PLYR\0\0\0\x14");
+    /// data.extend_from_slice(&[0, 0, 0x03, 0xE8, 0, 0, 0, 0, 0, 0, 0, 0x05, 1, 0, 0, 0]);
+    /// data.extend_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+    /// data.extend_from_slice(b"CNTR\0\0\0\xA8");
+    /// data.extend_from_slice(&[0; 0xA8]);
+    /// // Followed by data that isn't part of the game data
+    /// data.extend_from_slice(b"TAIL");
+    /// data.resize(BLOCK_SIZE, 0);
+    ///
+    /// let mut gci = Gci {
+    ///     header: GciHeader::from_bytes(&[0; HEADER_SIZE])?,
+    ///     data,
+    /// };
+    /// let mut save = SaveGame::from_gci(&gci)?;
+    /// assert_eq!(save.socks, 5);
+    ///
+    /// save.socks = 80;
+    /// let before = gci.data.clone();
+    /// save.write_to_gci(&mut gci)?;
+    /// assert_eq!(SaveGame::from_gci(&gci)?, save);
+    ///
+    /// // Only the checksum and the socks have changed
+    /// let changed: Vec<_> = (0..BLOCK_SIZE).filter(|&i| gci.data[i] != before[i]).collect();
+    /// assert!(changed.iter().all(|&i| (0x8..0xC).contains(&i) || (0x34..0x38).contains(&i)));
+    /// assert_eq!(&gci.data[0x3C..0x40], &[0xDE, 0xAD, 0xBE, 0xEF]);
+    /// assert_eq!(&gci.data[0xF0..0xF4], b"TAIL");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if `gci` does not already contain BfBB game data or if the new data is too large
+    /// to fit in the file.
+    pub fn write_to_gci(&self, gci: &mut Gci) -> SaveResult<()> {
+        let offset = find_game_data(&gci.data)?;
+        let region = &mut gci.data[offset..];
+        let mut chunks = read_chunks(region)?;
+        for (tag, payload) in self.chunks() {
+            match chunks.iter_mut().find(|(t, _)| *t == tag) {
+                Some((_, existing)) if existing.len() >= payload.len() => {
+                    existing[..payload.len()].copy_from_slice(&payload);
+                }
+                Some((_, existing)) => *existing = payload,
+                None => chunks.push((tag, payload)),
+            }
+        }
+
+        // Game data that grows may only replace the padding that follows it
+        let old_len = GAME_DATA_HEADER_SIZE + read_u32(region, 4) as usize;
+        let padding = region[old_len..].iter().take_while(|&&b| b == 0).count();
+        let bytes = write_chunks(&chunks);
+        if bytes.len() > old_len + padding {
+            return Err(Error::DataTooLarge {
+                required: bytes.len(),
+                available: old_len + padding,
+            });
+        }
+        region[..bytes.len()].copy_from_slice(&bytes);
+        if bytes.len() < old_len {
+            region[bytes.len()..old_len].fill(0);
+        }
+        Ok(())
+    }
+
+    /// Decode a save from game data, starting with its `GDAT` tag.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if `bytes` is not valid game data or is missing any required chunks.
+    ///
+    /// # Examples
+    ///
+    /// Game data assembled byte-for-byte, with its checksum calculated independently of this crate:
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use bfbb::save::{Error, SaveGame};
+    /// use bfbb::{Level, Spatula, TaskStatus};
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let mut chunks = Vec::new();
+    /// chunks.extend_from_slice(b"LEDR\0\0\0\x04");
+    /// chunks.extend_from_slice(&[0x00, 0x00, 0x0E, 0x10]); // Play time
+    /// chunks.extend_from_slice(b"ROOM\0\0\0\x04HB01");
+    /// chunks.extend_from_slice(b"PLYR\0\0\0\x10");
+    /// chunks.extend_from_slice(&[0x00, 0x00, 0x03, 0xE8]); // Shiny objects
+    /// chunks.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // Spatulas
+    /// chunks.extend_from_slice(&[0x00, 0x00, 0x00, 0x05]); // Socks
+    /// chunks.extend_from_slice(&[0x01, 0x00, 0x00, 0x00]); // Powers
+    /// chunks.extend_from_slice(b"CNTR\0\0\0\xA8");
+    /// chunks.extend_from_slice(&[0x00, 0x02]); // On Top of the Pineapple
+    /// chunks.extend_from_slice(&[0x00; 0xA6]);
+    ///
+    /// let mut bytes = b"GDAT\0\0\0\xE0".to_vec();
+    /// bytes.extend_from_slice(&[0x3D, 0xAF, 0x42, 0x31]);
+    /// bytes.extend_from_slice(&chunks);
+    ///
+    /// let save = SaveGame::from_game_data(&bytes)?;
+    /// assert_eq!(save.play_time, Duration::from_secs(3600));
+    /// assert_eq!(save.level, Level::BikiniBottom);
+    /// assert_eq!(save.shiny_objects, 1000);
+    /// assert_eq!(save.socks, 5);
+    /// assert!(save.powers.bubble_bowl && !save.powers.cruise_bubble);
+    /// assert_eq!(save.tasks[&Spatula::OnTopOfThePineapple], TaskStatus::Complete);
+    /// assert_eq!(save.spatula_count(), 1);
+    /// assert_eq!(save.to_game_data(), bytes);
+    ///
+    /// // The checksum is CRC-32/BZIP2, whose standard check value is 0xFC891918
+    /// let mut bytes = b"GDAT\0\0\0\x09\0\0\0\0".to_vec();
+    /// bytes.extend_from_slice(b"123456789");
+    /// assert!(matches!(
+    ///     SaveGame::from_game_data(&bytes),
+    ///     Err(Error::ChecksumMismatch { found: 0xFC89_1918, .. })
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_game_data(bytes: &[u8]) -> SaveResult<Self> {
+        let chunks = read_chunks(bytes)?;
+        let chunk = |tag: &[u8; 4], len: usize| {
+            let (_, payload) = chunks
+                .iter()
+                .find(|(t, _)| t == tag)
+                .ok_or(Error::MissingChunk(*tag))?;
+            payload.get(..len).ok_or(Error::InvalidChunk(*tag))
+        };
+
+        let leader = chunk(LEADER_TAG, LEADER_SIZE)?;
+        let play_time = Duration::from_secs(read_u32(leader, 0).into());
+
+        let room = chunk(ROOM_TAG, ROOM_SIZE)?;
+        let scene_id = SceneId(room.try_into().unwrap());
+        let level = Level::try_from(scene_id).map_err(|e| Error::InvalidLevel(e.0))?;
+
+        let player = chunk(PLAYER_TAG, PLAYER_SIZE)?;
+        let shiny_objects = read_u32(player, 0);
+        let socks = read_u32(player, 8);
+        let powers = Powers {
+            bubble_bowl: player[0xC] != 0,
+            cruise_bubble: player[0xD] != 0,
+        };
+
+        let counters = chunk(COUNTER_TAG, COUNTER_SIZE)?;
+        let tasks = Spatula::iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let count = i16::from_be_bytes([counters[i * 2], counters[i * 2 + 1]]);
+                let status =
+                    TaskStatus::try_from(count).map_err(|_| Error::InvalidTaskStatus(s, count))?;
+                Ok((s, status))
+            })
+            .collect::<SaveResult<_>>()?;
+
+        Ok(Self {
+            tasks,
+            socks,
+            shiny_objects,
+            level,
+            powers,
+            play_time,
+        })
+    }
+
+    /// Encode this save as game data, starting with its `GDAT` tag.
+    ///
+    /// This only contains the chunks represented by [`SaveGame`], use [`write_to_gci`](Self::write_to_gci)
+    /// to update a complete save file.
+    #[must_use]
+    pub fn to_game_data(&self) -> Vec<u8> {
+        write_chunks(&self.chunks())
+    }
+
+    fn chunks(&self) -> Vec<([u8; 4], Vec<u8>)> {
+        let play_time = u32::try_from(self.play_time.as_secs()).unwrap_or(u32::MAX);
+        let leader = play_time.to_be_bytes().to_vec();
+
+        let room = <[u8; 4]>::from(self.level).to_vec();
+
+        let mut player = Vec::with_capacity(PLAYER_SIZE);
+        player.extend_from_slice(&self.shiny_objects.to_be_bytes());
+        player.extend_from_slice(&self.spatula_count().to_be_bytes());
+        player.extend_from_slice(&self.socks.to_be_bytes());
+        player.push(self.powers.bubble_bowl.into());
+        player.push(self.powers.cruise_bubble.into());
+        player.extend_from_slice(&[0; 2]);
+
+        let counters = Spatula::iter()
+            .flat_map(|s| {
+                let status = self.tasks.get(&s).copied().unwrap_or_default();
+                i16::from(status).to_be_bytes()
+            })
+            .collect();
+
+        vec![
+            (*LEADER_TAG, leader),
+            (*ROOM_TAG, room),
+            (*PLAYER_TAG, player),
+            (*COUNTER_TAG, counters),
+        ]
+    }
+}
+
+/// Find the offset of the `GDAT` tag within a file's data.
+fn find_game_data(data: &[u8]) -> SaveResult<usize> {
+    (0..data.len().saturating_sub(GAME_DATA_HEADER_SIZE))
+        .step_by(4)
+        .find(|&i| &data[i..i + 4] == GAME_DATA_TAG)
+        .ok_or(Error::MissingGameData)
+}
+
+/// Split game data into its chunks, validating the checksum.
+fn read_chunks(bytes: &[u8]) -> SaveResult<Vec<([u8; 4], Vec<u8>)>> {
+    let header = bytes
+        .get(..GAME_DATA_HEADER_SIZE)
+        .ok_or(Error::UnexpectedEof)?;
+    if &header[..4] != GAME_DATA_TAG {
+        return Err(Error::MissingGameData);
+    }
+    let len = read_u32(header, 4) as usize;
+    let expected = read_u32(header, 8);
+    let mut data = bytes
+        .get(GAME_DATA_HEADER_SIZE..GAME_DATA_HEADER_SIZE + len)
+        .ok_or(Error::UnexpectedEof)?;
+
+    let found = crc32(data);
+    if found != expected {
+        return Err(Error::ChecksumMismatch { expected, found });
+    }
+
+    let mut chunks = Vec::new();
+    while !data.is_empty() {
+        let header = data.get(..8).ok_or(Error::UnexpectedEof)?;
+        let tag: [u8; 4] = header[..4].try_into().unwrap();
+        let len = read_u32(header, 4) as usize;
+        let payload = data.get(8..8 + len).ok_or(Error::InvalidChunk(tag))?;
+        chunks.push((tag, payload.to_vec()));
+        data = &data[8 + len..];
+    }
+    Ok(chunks)
+}
+
+/// Join chunks into game data, calculating the checksum.
+fn write_chunks(chunks: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (tag, payload) in chunks {
+        data.extend_from_slice(tag);
+        data.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        data.extend_from_slice(payload);
+    }
+
+    let mut bytes = Vec::with_capacity(GAME_DATA_HEADER_SIZE + data.len());
+    bytes.extend_from_slice(GAME_DATA_TAG);
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&crc32(&data).to_be_bytes());
+    bytes.extend_from_slice(&data);
+    bytes
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// The same CRC-32 used by the game (`xUtil_crc_update`), which processes bits most-significant first.
+fn crc32(bytes: &[u8]) -> u32 {
+    let crc = bytes.iter().fold(u32::MAX, |mut crc, &b| {
+        crc ^= u32::from(b) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    });
+    !crc
+}
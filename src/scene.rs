@@ -0,0 +1,181 @@
+use bytemuck::{Pod, Zeroable};
+use thiserror::Error;
+
+use crate::{Level, World};
+
+/// The id of a scene, as stored by the game.
+///
+/// Unlike [`Level`], a `SceneId` can represent any scene, including menus, unused scenes and ids that don't
+/// correspond to any scene at all. It can be converted into a [`Level`] via [`TryFrom`].
+///
+/// # Examples
+/// ```
+/// use bfbb::{Level, SceneId, World};
+///
+/// let scene = SceneId(*b"KF01");
+/// assert_eq!(scene.to_string(), "KF01");
+/// assert_eq!(scene.world(), Some(World::KelpForest));
+/// assert_eq!(Level::try_from(scene), Ok(Level::KelpForest));
+///
+/// // Scenes that aren't a `Level` can still be identified
+/// let menu = SceneId(*b"MNU4");
+/// assert!(menu.is_known());
+/// assert_eq!(menu.world(), None);
+/// assert!(Level::try_from(menu).is_err());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[repr(transparent)]
+pub struct SceneId(pub [u8; 4]);
+
+impl SceneId {
+    /// Every scene present on the game disc, in the order of their archives.
+    pub const ALL: &'static [SceneId] = &[
+        SceneId(*b"B101"),
+        SceneId(*b"B201"),
+        SceneId(*b"B301"),
+        SceneId(*b"B302"),
+        SceneId(*b"B303"),
+        SceneId(*b"BB01"),
+        SceneId(*b"BB02"),
+        SceneId(*b"BB03"),
+        SceneId(*b"BB04"),
+        SceneId(*b"BC01"),
+        SceneId(*b"BC02"),
+        SceneId(*b"BC03"),
+        SceneId(*b"BC04"),
+        SceneId(*b"BC05"),
+        SceneId(*b"DB01"),
+        SceneId(*b"DB02"),
+        SceneId(*b"DB03"),
+        SceneId(*b"DB04"),
+        SceneId(*b"DB06"),
+        SceneId(*b"GL01"),
+        SceneId(*b"GL02"),
+        SceneId(*b"GL03"),
+        SceneId(*b"GY01"),
+        SceneId(*b"GY02"),
+        SceneId(*b"GY03"),
+        SceneId(*b"GY04"),
+        SceneId(*b"HB00"),
+        SceneId(*b"HB01"),
+        SceneId(*b"HB02"),
+        SceneId(*b"HB03"),
+        SceneId(*b"HB04"),
+        SceneId(*b"HB05"),
+        SceneId(*b"HB06"),
+        SceneId(*b"HB07"),
+        SceneId(*b"HB08"),
+        SceneId(*b"HB09"),
+        SceneId(*b"HB10"),
+        SceneId(*b"JF01"),
+        SceneId(*b"JF02"),
+        SceneId(*b"JF03"),
+        SceneId(*b"JF04"),
+        SceneId(*b"KF01"),
+        SceneId(*b"KF02"),
+        SceneId(*b"KF04"),
+        SceneId(*b"KF05"),
+        SceneId(*b"MNU3"),
+        SceneId(*b"MNU4"),
+        SceneId(*b"PG12"),
+        SceneId(*b"RB01"),
+        SceneId(*b"RB02"),
+        SceneId(*b"RB03"),
+        SceneId(*b"SM01"),
+        SceneId(*b"SM02"),
+        SceneId(*b"SM03"),
+        SceneId(*b"SM04"),
+    ];
+
+    /// Returns `true` if this is one of the scenes present on the game disc (see [`SceneId::ALL`]).
+    #[must_use]
+    pub fn is_known(&self) -> bool {
+        Self::ALL.contains(self)
+    }
+
+    /// Returns the world of this scene.
+    ///
+    /// For a [`Level`] this is the same as [`Level::world`]. Any other scene id, such as an unused scene, is
+    /// determined by its first two characters. This is `None` for menus, the intro cutscene and the Spongeball Arena.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::{Level, SceneId, World};
+    ///
+    /// assert_eq!(SceneId(*b"HB01").world(), Some(World::BikiniBottom));
+    /// assert_eq!(SceneId(*b"HB00").world(), Level::IntroCutscene.world());
+    /// // An unused scene
+    /// assert_eq!(SceneId(*b"KF03").world(), Some(World::KelpForest));
+    /// ```
+    #[must_use]
+    pub fn world(&self) -> Option<World> {
+        if let Some(level) = self.level() {
+            return level.world();
+        }
+        match &self.0[..2] {
+            b"HB" => Some(World::BikiniBottom),
+            b"JF" => Some(World::JellyfishFields),
+            b"BB" => Some(World::DowntownBikiniBottom),
+            b"GL" => Some(World::GooLagoon),
+            b"B1" => Some(World::Poseidome),
+            b"RB" => Some(World::RockBottom),
+            b"BC" => Some(World::Mermalair),
+            b"SM" => Some(World::SandMountain),
+            b"B2" => Some(World::IndustrialPark),
+            b"KF" => Some(World::KelpForest),
+            b"GY" => Some(World::FlyingDutchmansGraveyard),
+            b"DB" => Some(World::SpongebobsDream),
+            b"B3" => Some(World::ChumBucketLab),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`Level`] for this scene, if it is one.
+    #[must_use]
+    pub fn level(&self) -> Option<Level> {
+        Level::try_from(*self).ok()
+    }
+}
+
+impl std::fmt::Debug for SceneId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SceneId({self})")
+    }
+}
+
+impl std::fmt::Display for SceneId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for &b in &self.0 {
+            if b.is_ascii_graphic() {
+                write!(f, "{}", b as char)?;
+            } else {
+                write!(f, "\\x{b:02X}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<[u8; 4]> for SceneId {
+    fn from(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<SceneId> for [u8; 4] {
+    fn from(scene: SceneId) -> Self {
+        scene.0
+    }
+}
+
+impl From<Level> for SceneId {
+    fn from(level: Level) -> Self {
+        Self(level.into())
+    }
+}
+
+/// Error type for converting a [`SceneId`] into a [`Level`].
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("Scene '{0}' is not a level")]
+pub struct NotALevelError(pub SceneId);
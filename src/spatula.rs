@@ -1,6 +1,12 @@
+use std::str::FromStr;
+
+use strum::IntoEnumIterator;
 use strum_macros::{EnumCount, EnumIter};
 
-use crate::Level;
+use crate::{
+    parse::{parse, ParseError},
+    Level, World,
+};
 
 /// Convenient definition for every spatula in the game
 ///
@@ -225,7 +231,8 @@ impl Spatula {
     ///
     /// **NOTE:** These indexes are only valid while the spatula's level is loaded.
     ///
-    /// **NOTE:** This list is currently only validated for the Gamecube version of the game.
+    /// **NOTE:** This list is currently only validated for the Gamecube version of the game. Tables for other builds
+    /// can be generated from their level archives with [`SpatulaTable`](crate::hip::scene::SpatulaTable).
     pub fn get_offset(&self) -> Option<usize> {
         #[allow(clippy::match_same_arms)]
         match *self {
@@ -414,6 +421,186 @@ impl Spatula {
             Self::TheSmallShallRuleOrNot => Level::ChumBucketBrain,
         }
     }
+
+    /// Returns the world this spatula is in.
+    ///
+    /// ```
+    /// use bfbb::{Spatula, World};
+    ///
+    /// assert_eq!(Spatula::SlideLeap.world(), World::JellyfishFields);
+    /// ```
+    #[must_use]
+    pub fn world(&self) -> World {
+        let (world, _) = (*self).into();
+        World::iter()
+            .nth(world)
+            .expect("Every menu coordinate should belong to a world")
+    }
+}
+
+impl std::fmt::Display for Spatula {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match *self {
+            Spatula::OnTopOfThePineapple => write!(fmt, "On Top of the Pineapple"),
+            Spatula::OnTopOfShadyShoals => write!(fmt, "On Top of Shady Shoals"),
+            Spatula::OnTopOfTheChumBucket => write!(fmt, "On Top of the Chum Bucket"),
+            Spatula::SpongebobsCloset => write!(fmt, "SpongeBob's Closet"),
+            Spatula::AnnoySquidward => write!(fmt, "Annoy Squidward"),
+            Spatula::AmbushAtTheTreeDome => write!(fmt, "Ambush at the Tree Dome"),
+            Spatula::InfestationAtTheKrustyKrab => write!(fmt, "Infestation at the Krusty Krab"),
+            Spatula::AWallJumpInTheBucket => write!(fmt, "A Wall Jump in the Bucket"),
+            Spatula::TopOfTheHill => write!(fmt, "Top of the Hill"),
+            Spatula::CowaBungee => write!(fmt, "Cowa-Bungee!"),
+            Spatula::Spelunking => write!(fmt, "Spelunking"),
+            Spatula::PatricksDilemma => write!(fmt, "Patrick's Dilemma"),
+            Spatula::NavigateTheCanyonsAndMesas => write!(fmt, "Navigate the Canyons and Mesas"),
+            Spatula::DrainTheLake => write!(fmt, "Drain the Lake"),
+            Spatula::SlideLeap => write!(fmt, "Slide Leap"),
+            Spatula::DefeatKingJellyfish => write!(fmt, "Defeat King Jellyfish"),
+            Spatula::EndOfTheRoad => write!(fmt, "End of the Road"),
+            Spatula::LearnSandysMoves => write!(fmt, "Learn Sandy's Moves"),
+            Spatula::TikisGoBoom => write!(fmt, "Tikis Go Boom"),
+            Spatula::AcrossTheRooftops => write!(fmt, "Across the Rooftops"),
+            Spatula::SwinginSandy => write!(fmt, "Swingin' Sandy"),
+            Spatula::AmbushInTheLighthouse => write!(fmt, "Ambush in the Lighthouse"),
+            Spatula::ExtremeBungee => write!(fmt, "Extreme Bungee"),
+            Spatula::ComeBackWithTheCruiseBubble => write!(fmt, "Come Back with the Cruise Bubble"),
+            Spatula::KingOfTheCastle => write!(fmt, "King of the Castle"),
+            Spatula::ConnectTheTowers => write!(fmt, "Connect the Towers"),
+            Spatula::SaveTheChildren => write!(fmt, "Save the Children"),
+            Spatula::OverTheMoat => write!(fmt, "Over the Moat"),
+            Spatula::ThroughTheSeaCaves => write!(fmt, "Through the Sea Caves"),
+            Spatula::CleanOutTheBumperBoats => write!(fmt, "Clean out the Bumper Boats"),
+            Spatula::SlipAndSlideUnderThePier => write!(fmt, "Slip and Slide Under the Pier"),
+            Spatula::TowerBungee => write!(fmt, "Tower Bungee"),
+            Spatula::RumbleAtThePoseidome => write!(fmt, "Rumble at the Poseidome"),
+            Spatula::GetToTheMuseum => write!(fmt, "Get to the Museum"),
+            Spatula::SlipSlidingAway => write!(fmt, "Slip Sliding Away"),
+            Spatula::ReturnTheMuseumsArt => write!(fmt, "Return the Museum's Art"),
+            Spatula::SwingalongSpatula => write!(fmt, "Swingalong Spatula"),
+            Spatula::PlunderingRobotsInTheMuseum => write!(fmt, "Plundering Robots in the Museum"),
+            Spatula::AcrossTheTrenchOfDarkness => write!(fmt, "Across the Trench of Darkness"),
+            Spatula::LasersAreFunAndGoodForYou => write!(fmt, "Lasers are Fun and Good for You"),
+            Spatula::HowInTarnationDoYouGetThere => {
+                write!(fmt, "How in Tarnation Do You Get There?")
+            }
+            Spatula::TopOfTheEntranceAreaML => write!(fmt, "Top of the Entrance Area"),
+            Spatula::TopOfTheComputerArea => write!(fmt, "Top of the Computer Area"),
+            Spatula::ShutDownTheSecuritySystem => write!(fmt, "Shut Down the Security System"),
+            Spatula::TheFunnelMachines => write!(fmt, "The Funnel Machines"),
+            Spatula::TheSpinningTowersOfPower => write!(fmt, "The Spinning Towers of Power"),
+            Spatula::TopOfTheSecurityTunnel => write!(fmt, "Top of the Security Tunnel"),
+            Spatula::CompleteTheRollingBallRoom => write!(fmt, "Complete the Rolling Ball Room"),
+            Spatula::DefeatPrawn => write!(fmt, "Defeat Prawn"),
+            Spatula::FrostyBungee => write!(fmt, "Frosty Bungee"),
+            Spatula::TopOfTheLodge => write!(fmt, "Top of the Lodge"),
+            Spatula::DefeatRobotsOnGuppyMound => write!(fmt, "Defeat Robots on Guppy Mound"),
+            Spatula::BeatMrsPuffsTime => write!(fmt, "Beat Mrs. Puff's Time"),
+            Spatula::DefeatRobotsOnFlounderHill => write!(fmt, "Defeat Robots on Flounder Hill"),
+            Spatula::BeatBubbleBuddysTime => write!(fmt, "Beat Bubble Buddy's Time"),
+            Spatula::DefeatRobotsOnSandMountain => write!(fmt, "Defeat Robots on Sand Mountain"),
+            Spatula::BeatLarrysTime => write!(fmt, "Beat Larry's Time"),
+            Spatula::RoboPatrickAhoy => write!(fmt, "Robo-Patrick Ahoy!"),
+            Spatula::ThroughTheWoods => write!(fmt, "Through the Woods"),
+            Spatula::FindAllTheLostCampers => write!(fmt, "Find All the Lost Campers"),
+            Spatula::TikiRoundup => write!(fmt, "Tiki Roundup"),
+            Spatula::DownInTheSwamp => write!(fmt, "Down in the Swamp"),
+            Spatula::ThroughTheKelpCaves => write!(fmt, "Through the Kelp Caves"),
+            Spatula::PowerCrystalCrisis => write!(fmt, "Power Crystal Crisis"),
+            Spatula::KelpVineSlide => write!(fmt, "Kelp Vine Slide"),
+            Spatula::BeatMermaidMansTime => write!(fmt, "Beat Mermaid Man's Time"),
+            Spatula::TopOfTheEntranceAreaFDG => write!(fmt, "Top of the Entrance Area"),
+            Spatula::APathThroughTheGoo => write!(fmt, "A Path through the Goo"),
+            Spatula::GooTankerAhoy => write!(fmt, "Goo Tanker Ahoy!"),
+            Spatula::TopOfTheStackOfShips => write!(fmt, "Top of the Stack of Ships"),
+            Spatula::ShipwreckBungee => write!(fmt, "Shipwreck Bungee"),
+            Spatula::DestroyTheRobotShip => write!(fmt, "Destroy the Robot Ship"),
+            Spatula::GetAloftThereMatey => write!(fmt, "Get Aloft There, Matey!"),
+            Spatula::DefeatTheFlyingDutchman => write!(fmt, "Defeat the Flying Dutchman"),
+            Spatula::AcrossTheDreamscape => write!(fmt, "Across the Dreamscape"),
+            Spatula::FollowTheBouncingBall => write!(fmt, "Follow the Bouncing Ball"),
+            Spatula::SlidingTexasStyle => write!(fmt, "Sliding Texas Style"),
+            Spatula::SwingersAhoy => write!(fmt, "Swingers Ahoy"),
+            Spatula::MusicIsInTheEarOfTheBeholder => {
+                write!(fmt, "Music is in the Ear of the Beholder")
+            }
+            Spatula::KrabbyPattyPlatforms => write!(fmt, "Krabby Patty Platforms"),
+            Spatula::SuperBounce => write!(fmt, "Super Bounce"),
+            Spatula::HereYouGo => write!(fmt, "Here You Go"),
+            Spatula::KahRahTae => write!(fmt, "Kah - Rah - Tae!"),
+            Spatula::TheSmallShallRuleOrNot => write!(fmt, "The Small Shall Rule... Or Not"),
+        }
+    }
+}
+
+impl FromStr for Spatula {
+    type Err = ParseError<Spatula>;
+
+    /// Parse a spatula from its in-game task name or its identifier, ignoring case, whitespace and punctuation.
+    ///
+    /// Small typos are tolerated as long as only one spatula is a close match.
+    ///
+    /// ```
+    /// use bfbb::Spatula;
+    ///
+    /// assert_eq!("annoy squidward".parse(), Ok(Spatula::AnnoySquidward));
+    /// assert_eq!("KahRahTae".parse(), Ok(Spatula::KahRahTae));
+    /// assert_eq!("lasers are fun".parse(), Ok(Spatula::LasersAreFunAndGoodForYou));
+    /// assert_eq!("spelnking".parse(), Ok(Spatula::Spelunking));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(
+            s,
+            Spatula::iter().map(|spatula| (spatula, [spatula.to_string(), format!("{spatula:?}")])),
+        )
+    }
+}
+
+/// The state of a task as shown by its icon in the pause menu.
+///
+/// This is the value of the `count` field of a task's `_xCounter` struct, both in memory and in save files.
+///
+/// ```
+/// use bfbb::TaskStatus;
+///
+/// let status = TaskStatus::try_from(2).expect("'2' should be a completed task");
+/// assert_eq!(status, TaskStatus::Complete);
+/// assert_eq!(i16::from(TaskStatus::Locked), 0);
+/// ```
+#[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[repr(i16)]
+pub enum TaskStatus {
+    /// Task is "locked", will be a question mark in the menu.
+    #[default]
+    Locked = 0,
+    /// Task is "incomplete", will be a silver spatula in the menu.
+    Incomplete = 1,
+    /// Task is "complete", will be a golden spatula in the menu.
+    Complete = 2,
+    /// Task is also silver in the menu, this appears to only be used by [`Spatula::InfestationAtTheKrustyKrab`],
+    /// which uses this value for after clearing the robots, but before you've collected the spatula.
+    Cleared = 3,
+}
+
+impl TryFrom<i16> for TaskStatus {
+    type Error = &'static str;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Locked),
+            1 => Ok(Self::Incomplete),
+            2 => Ok(Self::Complete),
+            3 => Ok(Self::Cleared),
+            _ => Err("Counter value did not correspond to a TaskStatus"),
+        }
+    }
+}
+
+impl From<TaskStatus> for i16 {
+    fn from(status: TaskStatus) -> Self {
+        status as i16
+    }
 }
 
 impl TryFrom<(usize, usize)> for Spatula {
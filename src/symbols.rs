@@ -0,0 +1,435 @@
+//! Resolving addresses from the symbols of the [decomp](https://github.com/bfbbdecomp/bfbb).
+//!
+//! The decomp names the game's functions and globals, and records their addresses in the retail executable. A
+//! [`SymbolMap`] can be read from either of its formats:
+//!
+//! - A [decomp-toolkit](https://github.com/encounter/decomp-toolkit) `symbols.txt`, with lines such as
+//!   `main = .text:0x80006C00; // type:function size:0x1A4`.
+//! - A CodeWarrior linker map (`.map`), as written by the linker when building the game.
+//!
+//! Variables can then be located by a symbol name plus an offset into it (e.g. `"globals+0x1C"`) instead of a magic
+//! number. With the `game-interface` feature, [`SymbolMap::verify`] checks that the map matches the code of the
+//! running game before any of its addresses are trusted.
+//!
+//! # Examples
+//! ```
+//! use bfbb::symbols::{SymbolKind, SymbolMap};
+//!
+//! # fn main() -> Result<(), bfbb::symbols::Error> {
+//! let map = SymbolMap::parse(
+//!     "\
+//! sExampleFunc = .text:0x80010000; // type:function size:0x40 scope:global
+//! sExampleData = .bss:0x80300000; // type:object size:0x100 scope:local
+//! ",
+//! )?;
+//! assert_eq!(map.get("sExampleFunc").unwrap().kind, SymbolKind::Function);
+//! assert_eq!(map.resolve("sExampleData")?, 0x8030_0000);
+//! assert_eq!(map.resolve("sExampleData+0x1C")?, 0x8030_001C);
+//! assert_eq!(map.path("sExampleData+0x8", &[0x4])?, vec![0x8030_0008, 0x4]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::HashMap, path::Path};
+
+use thiserror::Error;
+
+#[cfg(feature = "game-interface")]
+use crate::game_interface::{game_var::InterfaceBackend, GameInterface, InterfaceError};
+
+/// Result type for reading and resolving symbols.
+pub type SymbolResult<T> = std::result::Result<T, Error>;
+
+/// Error type for failures to read or resolve symbols.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The symbol map could not be read from disk.
+    #[error("Symbol map could not be read")]
+    Io(#[from] std::io::Error),
+    /// A line of the symbol map could not be parsed.
+    #[error("Line {0} is not a valid symbol")]
+    Syntax(usize),
+    /// There is no symbol with the given name.
+    #[error("No symbol named '{0}'")]
+    Unknown(String),
+    /// More than one symbol has the given name (e.g. `static` symbols from different files).
+    #[error("More than one symbol is named '{0}'")]
+    Ambiguous(String),
+    /// An expression is not of the form `name` or `name+offset`.
+    #[error("'{0}' is not a valid symbol expression")]
+    InvalidExpression(String),
+    /// An offset lies beyond the end of its symbol.
+    #[error("Offset {offset:#X} is outside of '{symbol}'")]
+    OutOfBounds {
+        /// The name of the symbol.
+        symbol: String,
+        /// The offset into the symbol.
+        offset: u32,
+    },
+    /// The map has no functions to check against the game's code.
+    #[error("Symbol map has no functions to verify")]
+    NoFunctions,
+    /// The map does not match the code of the running game.
+    #[error("Symbol map does not match the game's code ({matched} of {total} functions matched)")]
+    CodeMismatch {
+        /// The number of functions that matched.
+        matched: usize,
+        /// The number of functions that were checked.
+        total: usize,
+    },
+    /// The game could not be accessed.
+    #[cfg(feature = "game-interface")]
+    #[error(transparent)]
+    Interface(#[from] InterfaceError),
+}
+
+/// What a [`Symbol`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// Executable code.
+    Function,
+    /// Data, such as a global variable.
+    Object,
+    /// A label with no size.
+    Label,
+    /// The map does not say.
+    Unknown,
+}
+
+/// A named address in the game's executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// The name of this symbol.
+    pub name: String,
+    /// The section containing this symbol (e.g. `.text` or `.bss`), if known.
+    pub section: Option<String>,
+    /// The virtual address of this symbol.
+    pub addr: u32,
+    /// The size of this symbol in bytes, or 0 if unknown.
+    pub size: u32,
+    /// What this symbol refers to.
+    pub kind: SymbolKind,
+}
+
+/// The symbols of the game's executable, looked up by name.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolMap {
+    symbols: Vec<Symbol>,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl SymbolMap {
+    /// Read a `symbols.txt` or linker map file, detecting its format from its contents.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if the file can not be read or parsed.
+    pub fn open(path: impl AsRef<Path>) -> SymbolResult<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse either a `symbols.txt` or a linker map, detecting its format from its contents.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error::Syntax`] if a line can not be parsed.
+    pub fn parse(text: &str) -> SymbolResult<Self> {
+        if text.contains("section layout") {
+            Ok(Self::from_linker_map(text))
+        } else {
+            Self::from_symbols_txt(text)
+        }
+    }
+
+    /// Parse a decomp-toolkit `symbols.txt`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error::Syntax`] if a line can not be parsed or its symbol extends past the end of the address
+    /// space.
+    pub fn from_symbols_txt(text: &str) -> SymbolResult<Self> {
+        let symbols = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with("//"))
+            .map(|(i, line)| parse_symbols_txt_line(line).ok_or(Error::Syntax(i + 1)))
+            .collect::<SymbolResult<_>>()?;
+        Ok(Self::from_symbols(symbols))
+    }
+
+    /// Parse a CodeWarrior linker map.
+    ///
+    /// Only the `section layout` tables are read. Symbols that were stripped by the linker (`UNUSED`) are skipped, as
+    /// are lines that can't be parsed, since the format varies between linker versions.
+    #[must_use]
+    pub fn from_linker_map(text: &str) -> Self {
+        let mut symbols = Vec::new();
+        let mut section = None;
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if let Some(name) = trimmed.strip_suffix(" section layout") {
+                section = Some(name.to_owned());
+            } else if trimmed.ends_with(':') || trimmed.starts_with("Memory map") {
+                section = None;
+            } else if let Some(section) = &section {
+                symbols.extend(parse_linker_map_line(trimmed, section));
+            }
+        }
+        Self::from_symbols(symbols)
+    }
+
+    fn from_symbols(symbols: Vec<Symbol>) -> Self {
+        let mut by_name: HashMap<_, Vec<_>> = HashMap::new();
+        for (i, symbol) in symbols.iter().enumerate() {
+            by_name.entry(symbol.name.clone()).or_default().push(i);
+        }
+        Self { symbols, by_name }
+    }
+
+    /// The symbol named `name`, if there is exactly one.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        match self.by_name.get(name)?.as_slice() {
+            [i] => Some(&self.symbols[*i]),
+            _ => None,
+        }
+    }
+
+    /// Iterate over every symbol, in the order they appear in the map.
+    pub fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+
+    /// The number of symbols in the map.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns `true` if the map has no symbols.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Resolve an expression of the form `name` or `name+offset` to an address.
+    ///
+    /// The offset may be decimal or hexadecimal with a `0x` prefix, and must lie within the symbol if its size is
+    /// known.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if `expr` is malformed, the symbol does not exist or is ambiguous, or the offset is
+    /// out of bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::symbols::{Error, SymbolMap};
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let map = SymbolMap::parse("sUnsized = .bss:0xFFFFFFF0; // type:object")?;
+    /// assert_eq!(map.resolve("sUnsized+0x8")?, 0xFFFF_FFF8);
+    /// assert!(matches!(map.resolve("sUnsized+0x10"), Err(Error::OutOfBounds { .. })));
+    ///
+    /// // Symbols can't extend past the end of the address space
+    /// let overflow = SymbolMap::parse("sTooBig = .bss:0xFFFFFFF0; // type:object size:0x20");
+    /// assert!(matches!(overflow, Err(Error::Syntax(1))));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve(&self, expr: &str) -> SymbolResult<u32> {
+        let invalid = || Error::InvalidExpression(expr.to_owned());
+        let (name, offset) = match expr.split_once('+') {
+            Some((name, offset)) => (
+                name.trim(),
+                parse_number(offset.trim()).ok_or_else(invalid)?,
+            ),
+            None => (expr.trim(), 0),
+        };
+        if name.is_empty() {
+            return Err(invalid());
+        }
+
+        let symbol = match self.by_name.get(name).map(Vec::as_slice) {
+            Some([i]) => &self.symbols[*i],
+            Some(_) => return Err(Error::Ambiguous(name.to_owned())),
+            None => return Err(Error::Unknown(name.to_owned())),
+        };
+        let out_of_bounds = || Error::OutOfBounds {
+            symbol: name.to_owned(),
+            offset,
+        };
+        if symbol.size != 0 && offset >= symbol.size {
+            return Err(out_of_bounds());
+        }
+        symbol.addr.checked_add(offset).ok_or_else(out_of_bounds)
+    }
+
+    /// Resolve `expr` (see [`resolve`](Self::resolve)) and append `offsets` to create a pointer-path.
+    ///
+    /// See [`InterfaceBackend::create_var`](crate::game_interface::game_var::InterfaceBackend::create_var) for how
+    /// the path is followed.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if `expr` can not be resolved.
+    pub fn path(&self, expr: &str, offsets: &[usize]) -> SymbolResult<Vec<usize>> {
+        let addr = self.resolve(expr)? as usize;
+        Ok(std::iter::once(addr)
+            .chain(offsets.iter().copied())
+            .collect())
+    }
+}
+
+#[cfg(feature = "game-interface")]
+impl SymbolMap {
+    /// Check that this map matches the code of the game accessed by `interface`.
+    ///
+    /// Compiled functions end by branching elsewhere (`blr`, `b`, `rfi`, ...), so the last instruction of every
+    /// function in the map is read from the game and checked to be a branch. If the map is for a different build of
+    /// the game, its functions are misplaced and most of these checks fail. A small number of failures is tolerated,
+    /// since some hand-written functions don't end in a branch.
+    ///
+    /// # Examples
+    /// ```
+    /// use bfbb::game_interface::mock::MockInterface;
+    /// use bfbb::symbols::{Error, SymbolMap};
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let map = SymbolMap::parse("sExampleFunc = .text:0x80010000; // type:function size:0x8")?;
+    /// let mut interface = MockInterface::default();
+    /// assert!(matches!(map.verify(&interface), Err(Error::CodeMismatch { .. })));
+    ///
+    /// // `li r3, 0` then `blr`
+    /// interface.write_bytes(0x8001_0000, &[0x38, 0x60, 0, 0, 0x4E, 0x80, 0, 0x20])?;
+    /// map.verify(&interface)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error::CodeMismatch`] if fewer than 90% of functions end in a branch, an
+    /// [`Error::NoFunctions`] if the map has no functions with a known size, or an [`Error::Interface`] if the
+    /// implementation is unable to access the game.
+    pub fn verify<F: InterfaceBackend>(&self, interface: &GameInterface<F>) -> SymbolResult<()> {
+        // The address after the end of each function, skipping any that would wrap around the address space
+        let functions: Vec<_> = self
+            .symbols
+            .iter()
+            .filter(|s| s.kind == SymbolKind::Function && s.size >= 4)
+            .filter_map(|s| Some((s.addr, s.addr.checked_add(s.size)?)))
+            .collect();
+        let (Some(start), Some(end)) = (
+            functions.iter().map(|&(addr, _)| addr).min(),
+            functions.iter().map(|&(_, end)| end).max(),
+        ) else {
+            return Err(Error::NoFunctions);
+        };
+
+        // Read all of the code at once, rather than a word at a time
+        let mut code = vec![0; (end - start) as usize];
+        interface.read_bytes(start, &mut code)?;
+
+        let matched = functions
+            .iter()
+            .filter(|&&(_, func_end)| {
+                let i = (func_end - 4 - start) as usize;
+                let word = u32::from_be_bytes(code[i..i + 4].try_into().unwrap());
+                // Primary opcodes of `bc`, `b` and `bclr`/`bcctr`/`rfi`
+                matches!(word >> 26, 16 | 18 | 19)
+            })
+            .count();
+        let total = functions.len();
+        if matched * 10 < total * 9 {
+            return Err(Error::CodeMismatch { matched, total });
+        }
+        log::debug!("Symbol map verified ({matched} of {total} functions matched)");
+        Ok(())
+    }
+}
+
+/// Parse a number that is either decimal or hexadecimal with a `0x` prefix.
+fn parse_number(s: &str) -> Option<u32> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Parse a line such as `name = .text:0x80003100; // type:function size:0x140 scope:global`.
+fn parse_symbols_txt_line(line: &str) -> Option<Symbol> {
+    let (definition, attributes) = line.split_once("//").unwrap_or((line, ""));
+    let (name, location) = definition.split_once('=')?;
+    let name = name.trim();
+    let name = name
+        .strip_prefix('"')
+        .and_then(|n| n.strip_suffix('"'))
+        .unwrap_or(name);
+    let location = location.trim().strip_suffix(';')?.trim();
+    let (section, addr) = match location.rsplit_once(':') {
+        Some((section, addr)) => (Some(section.to_owned()), addr),
+        None => (None, location),
+    };
+
+    let mut symbol = Symbol {
+        name: name.to_owned(),
+        section,
+        addr: parse_number(addr)?,
+        size: 0,
+        kind: SymbolKind::Unknown,
+    };
+    for (key, value) in attributes
+        .split_whitespace()
+        .filter_map(|a| a.split_once(':'))
+    {
+        match key {
+            "size" => symbol.size = parse_number(value)?,
+            "type" => {
+                symbol.kind = match value {
+                    "function" => SymbolKind::Function,
+                    "object" => SymbolKind::Object,
+                    "label" => SymbolKind::Label,
+                    _ => SymbolKind::Unknown,
+                }
+            }
+            _ => {}
+        }
+    }
+    symbol.addr.checked_add(symbol.size)?;
+    Some(symbol)
+}
+
+/// Parse a line of a linker map's section layout, such as `00000000 000140 80003100  4 __start __start.o`.
+///
+/// Newer linkers add a file offset column after the virtual address.
+fn parse_linker_map_line(line: &str, section: &str) -> Option<Symbol> {
+    if line.contains("(entry of") {
+        return None;
+    }
+    let tokens: Vec<_> = line.split_whitespace().collect();
+    let hex = |s: &str| u32::from_str_radix(s, 16).ok();
+    let (size, addr) = (hex(tokens.get(1)?)?, hex(tokens.get(2)?)?);
+    addr.checked_add(size)?;
+    hex(tokens.first()?)?;
+    let name_index = if tokens.get(3)?.len() == 8 { 5 } else { 4 };
+    let name = *tokens.get(name_index)?;
+    // The section itself is listed as a symbol at the start of each object file
+    if name == section {
+        return None;
+    }
+
+    let kind = if matches!(section, ".init" | ".text") {
+        SymbolKind::Function
+    } else {
+        SymbolKind::Object
+    };
+    Some(Symbol {
+        name: name.to_owned(),
+        section: Some(section.to_owned()),
+        addr,
+        size,
+        kind,
+    })
+}
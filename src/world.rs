@@ -0,0 +1,154 @@
+use strum::IntoEnumIterator;
+use strum_macros::{EnumCount, EnumIter};
+
+use crate::{Level, Spatula};
+
+/// Convenient definition for every world in the game.
+///
+/// Worlds are the groups of levels shown in the pause menu, and the order of variants matches the level-index of
+/// the menu coordinates used by [`Spatula`].
+///
+/// # Examples
+/// ```
+/// use bfbb::{Level, Spatula, World};
+///
+/// assert_eq!(Level::JellyfishCaves.world(), Some(World::JellyfishFields));
+/// assert_eq!(Spatula::SpongebobsCloset.world(), World::BikiniBottom);
+///
+/// assert!(World::GooLagoon.levels().contains(&Level::GooLagoonPier));
+/// assert_eq!(World::ChumBucketLab.spatulas().count(), 2);
+/// ```
+#[derive(EnumIter, EnumCount, Hash, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum World {
+    #[doc(hidden)]
+    BikiniBottom,
+    #[doc(hidden)]
+    JellyfishFields,
+    #[doc(hidden)]
+    DowntownBikiniBottom,
+    #[doc(hidden)]
+    GooLagoon,
+    #[doc(hidden)]
+    Poseidome,
+    #[doc(hidden)]
+    RockBottom,
+    #[doc(hidden)]
+    Mermalair,
+    #[doc(hidden)]
+    SandMountain,
+    #[doc(hidden)]
+    IndustrialPark,
+    #[doc(hidden)]
+    KelpForest,
+    #[doc(hidden)]
+    FlyingDutchmansGraveyard,
+    #[doc(hidden)]
+    SpongebobsDream,
+    #[doc(hidden)]
+    ChumBucketLab,
+}
+
+impl World {
+    /// Returns every level in this world.
+    #[must_use]
+    pub fn levels(&self) -> &'static [Level] {
+        match *self {
+            Self::BikiniBottom => &[
+                Level::BikiniBottom,
+                Level::SpongebobHouse,
+                Level::SquidwardHouse,
+                Level::PatrickHouse,
+                Level::ShadyShoals,
+                Level::PoliceStation,
+                Level::Treedome,
+                Level::KrustyKrab,
+                Level::ChumBucket,
+                Level::Theater,
+            ],
+            Self::JellyfishFields => &[
+                Level::JellyfishRock,
+                Level::JellyfishCaves,
+                Level::JellyfishLake,
+                Level::JellyfishMountain,
+            ],
+            Self::DowntownBikiniBottom => &[
+                Level::DowntownStreets,
+                Level::DowntownRooftops,
+                Level::DowntownLighthouse,
+                Level::DowntownSeaNeedle,
+            ],
+            Self::GooLagoon => &[
+                Level::GooLagoonBeach,
+                Level::GooLagoonCaves,
+                Level::GooLagoonPier,
+            ],
+            Self::Poseidome => &[Level::Poseidome],
+            Self::RockBottom => &[
+                Level::RockBottomDowntown,
+                Level::RockBottomMuseum,
+                Level::RockBottomTrench,
+            ],
+            Self::Mermalair => &[
+                Level::MermalairEntranceArea,
+                Level::MermalairMainChamber,
+                Level::MermalairSecurityTunnel,
+                Level::MermalairBallroom,
+                Level::MermalairVillianContainment,
+            ],
+            Self::SandMountain => &[
+                Level::SandMountainHub,
+                Level::SandMountainSlide1,
+                Level::SandMountainSlide2,
+                Level::SandMountainSlide3,
+            ],
+            Self::IndustrialPark => &[Level::IndustrialPark],
+            Self::KelpForest => &[
+                Level::KelpForest,
+                Level::KelpSwamps,
+                Level::KelpCaves,
+                Level::KelpVines,
+            ],
+            Self::FlyingDutchmansGraveyard => &[
+                Level::GraveyardLake,
+                Level::GraveyardShipwreck,
+                Level::GraveyardShip,
+                Level::GraveyardBoss,
+            ],
+            Self::SpongebobsDream => &[
+                Level::SpongebobsDream,
+                Level::SandysDream,
+                Level::SquidwardsDream,
+                Level::KrabsDream,
+                Level::PatricksDream,
+            ],
+            Self::ChumBucketLab => &[Level::ChumBucketLab, Level::ChumBucketBrain],
+        }
+    }
+
+    /// Returns an iterator over every spatula in this world, in menu order.
+    pub fn spatulas(&self) -> impl Iterator<Item = Spatula> {
+        let world = *self;
+        Spatula::iter().filter(move |s| s.world() == world)
+    }
+}
+
+impl std::fmt::Display for World {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match *self {
+            World::BikiniBottom => write!(fmt, "Bikini Bottom"),
+            World::JellyfishFields => write!(fmt, "Jellyfish Fields"),
+            World::DowntownBikiniBottom => write!(fmt, "Downtown Bikini Bottom"),
+            World::GooLagoon => write!(fmt, "Goo Lagoon"),
+            World::Poseidome => write!(fmt, "Poseidome"),
+            World::RockBottom => write!(fmt, "Rock Bottom"),
+            World::Mermalair => write!(fmt, "Mermalair"),
+            World::SandMountain => write!(fmt, "Sand Mountain"),
+            World::IndustrialPark => write!(fmt, "Industrial Park"),
+            World::KelpForest => write!(fmt, "Kelp Forest"),
+            World::FlyingDutchmansGraveyard => write!(fmt, "Flying Dutchman's Graveyard"),
+            World::SpongebobsDream => write!(fmt, "SpongeBob's Dream"),
+            World::ChumBucketLab => write!(fmt, "Chum Bucket Lab"),
+        }
+    }
+}